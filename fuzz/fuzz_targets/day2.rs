@@ -0,0 +1,58 @@
+#![no_main]
+
+use advent_of_code::{
+    Level, ReportSafety, StepRange, check_report_safety, check_report_safety_one_mismatch,
+    explain_report,
+};
+use libfuzzer_sys::fuzz_target;
+
+/// Whether every step of the report is safe in the same direction, widened so
+/// that no difference can overflow.
+pub fn safe(report: &[i128], steps: StepRange) -> bool {
+    let safe_step = |diff: i128| (steps.min() as i128..=steps.max() as i128).contains(&diff);
+    report.windows(2).all(|x| safe_step(x[1] - x[0]))
+        || report.windows(2).all(|x| safe_step(x[0] - x[1]))
+}
+
+/// Try the report as it is and then with each level removed in turn.
+pub fn safe_one_mismatch(report: &[i128], steps: StepRange) -> bool {
+    safe(report, steps)
+        || (0..report.len()).any(|i| {
+            let mut removed = report.to_vec();
+            removed.remove(i);
+            safe(&removed, steps)
+        })
+}
+
+pub fn check<T: Level + Into<i128>>(report: &[T], steps: StepRange) {
+    let wide = report.iter().map(|&x| x.into()).collect::<Vec<i128>>();
+    let expected = safe_one_mismatch(&wide, steps);
+    assert_eq!(check_report_safety(report, steps), safe(&wide, steps));
+    assert_eq!(check_report_safety_one_mismatch(report, steps), expected);
+    assert_eq!(
+        !matches!(
+            explain_report(report, 1, steps),
+            ReportSafety::Unsafe { .. }
+        ),
+        expected
+    );
+}
+
+fuzz_target!(|data: (Vec<i8>, Vec<i64>, u8, u8)| {
+    let (narrow, wide, min, max) = data;
+    // Mostly small steps, so that safe reports are common, and now and then
+    // bounds past the narrow level type
+    let min = match min {
+        255 => 200,
+        min => (min % 4) as i64,
+    };
+    let max = match max {
+        255 => i64::MAX,
+        max => (max % 8) as i64,
+    };
+    let Some(steps) = StepRange::new(min, max) else {
+        return;
+    };
+    check(&narrow, steps);
+    check(&wide, steps);
+});
@@ -1,7 +1,7 @@
 #![no_main]
 #![feature(let_chains)]
 
-use advent_of_code::day9;
+use advent_of_code::{day9, parsers::day9_parser};
 use libfuzzer_sys::fuzz_target;
 
 pub fn expand(layout: Vec<u8>) -> Vec<Option<u64>> {
@@ -50,18 +50,17 @@ pub fn checksum(layout: Vec<Option<u64>>) -> u64 {
         .sum()
 }
 
-fuzz_target!(|data: Vec<u8>| {
-    if data.len() % 2 == 0 {
+fuzz_target!(|data: &[u8]| {
+    // Go through the real parser, so that the input is a disk map as text
+    // Disk maps without a file at the end are rejected
+    let Ok(layout) = day9_parser(data, &mut vec![]) else {
         return;
-    }
-    if data.iter().step_by(2).any(|x| *x == 0) {
-        return;
-    }
-    if *data.iter().max().unwrap() >= 10 {
+    };
+    if layout.iter().step_by(2).any(|x| *x == 0) {
         return;
     }
-    let actual = day9(data.clone());
-    let expanded = expand(data);
+    let actual = day9(layout.clone());
+    let expanded = expand(layout);
     let compressed = compress(expanded);
     let checksum = checksum(compressed);
     assert_eq!(actual.1, checksum);
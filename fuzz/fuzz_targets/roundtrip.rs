@@ -0,0 +1,26 @@
+#![no_main]
+
+use advent_of_code::{
+    generate::{Params, Rng},
+    solutions::DAYS,
+    writers::check_roundtrip,
+};
+use libfuzzer_sys::fuzz_target;
+
+// Start from a generated input, so that most cases get past the parser, and
+// splice the fuzzer's bytes into it before checking the round trip
+fuzz_target!(|data: (u8, u64, u16, u8, &[u8])| {
+    let (day, seed, at, removed, patch) = data;
+    let day = &DAYS[day as usize % DAYS.len()];
+    let params = Params {
+        size: Some(8),
+        width: Some(8),
+    };
+    let mut text = vec![];
+    (day.generate)(&mut Rng::new(seed), params, &mut text).unwrap();
+    let at = at as usize % (text.len() + 1);
+    let end = (at + removed as usize % 8).min(text.len());
+    text.splice(at..end, patch.iter().copied());
+
+    check_roundtrip(day.day, &text);
+});
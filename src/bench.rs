@@ -0,0 +1,142 @@
+use anyhow::Result;
+use std::{path::Path, time::Duration};
+
+use crate::{
+    parsers::Source,
+    report::{Format, csv_field, json_string},
+    solutions::{Entry, Part},
+};
+
+/// Summary statistics over the samples of one phase.
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub p95: Duration,
+}
+
+impl Stats {
+    /// Assumes there is at least one sample.
+    pub fn from_samples(samples: &mut [Duration]) -> Self {
+        samples.sort();
+        let n = samples.len();
+        let median = if n % 2 == 1 {
+            samples[n / 2]
+        } else {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2
+        };
+        // Nearest-rank percentile
+        let p95 = samples[(n * 95).div_ceil(100) - 1];
+        Stats {
+            min: samples[0],
+            median,
+            mean: samples.iter().sum::<Duration>() / n as u32,
+            p95,
+        }
+    }
+
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"min_ns\":{},\"median_ns\":{},\"mean_ns\":{},\"p95_ns\":{}}}",
+            self.min.as_nanos(),
+            self.median.as_nanos(),
+            self.mean.as_nanos(),
+            self.p95.as_nanos(),
+        )
+    }
+}
+
+/// Timings of the parse and solve phases of a day over repeated runs.
+pub struct Bench<'a> {
+    pub day: u8,
+    pub input: &'a Path,
+    pub runs: usize,
+    pub warmup: usize,
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+/// Run a day `warmup` times without recording, then `runs` times recording how
+/// long parsing and solving each took. The input is read exactly as given if
+/// `strict`.
+pub fn bench<'a>(
+    day: &Entry,
+    input: &'a Path,
+    strict: bool,
+    part: Option<Part>,
+    runs: usize,
+    warmup: usize,
+) -> Result<Bench<'a>> {
+    assert!(runs > 0);
+
+    for _ in 0..warmup {
+        (day.run)(Source::File(input.to_path_buf()).strict(strict), part, None)?;
+    }
+    let mut parse_times = Vec::with_capacity(runs);
+    let mut solve_times = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let run = (day.run)(Source::File(input.to_path_buf()).strict(strict), part, None)?;
+        parse_times.push(run.parse_time);
+        solve_times.push(run.solve_time);
+    }
+
+    Ok(Bench {
+        day: day.day,
+        input,
+        runs,
+        warmup,
+        parse: Stats::from_samples(&mut parse_times),
+        solve: Stats::from_samples(&mut solve_times),
+    })
+}
+
+impl Bench<'_> {
+    pub fn render(&self, format: Format) -> String {
+        let phases = [("parse", &self.parse), ("solve", &self.solve)];
+        match format {
+            Format::Plain => {
+                let mut out = format!(
+                    "{:<6} {:>12} {:>12} {:>12} {:>12}",
+                    "phase", "min", "median", "mean", "p95"
+                );
+                for (phase, stats) in phases {
+                    out += &format!(
+                        "\n{:<6} {:>12} {:>12} {:>12} {:>12}",
+                        phase,
+                        format!("{:.2?}", stats.min),
+                        format!("{:.2?}", stats.median),
+                        format!("{:.2?}", stats.mean),
+                        format!("{:.2?}", stats.p95),
+                    );
+                }
+                out
+            }
+            Format::Json => format!(
+                "{{\"day\":{},\"input\":{},\"runs\":{},\"warmup\":{},\"parse\":{},\"solve\":{}}}",
+                self.day,
+                json_string(&self.input.to_string_lossy()),
+                self.runs,
+                self.warmup,
+                self.parse.to_json(),
+                self.solve.to_json(),
+            ),
+            Format::Csv => phases
+                .iter()
+                .map(|(phase, stats)| {
+                    format!(
+                        "{},{},{},{},{},{},{},{}",
+                        self.day,
+                        phase,
+                        self.runs,
+                        stats.min.as_nanos(),
+                        stats.median.as_nanos(),
+                        stats.mean.as_nanos(),
+                        stats.p95.as_nanos(),
+                        csv_field(&self.input.to_string_lossy()),
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
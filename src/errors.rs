@@ -1,36 +1,276 @@
-use anyhow::{Result, anyhow};
-use std::{cell::RefCell, iter::Scan, rc::Rc};
+use anyhow::Result;
+use std::{
+    cell::RefCell,
+    fmt,
+    iter::{Flatten, Scan},
+    marker::PhantomData,
+    num::ParseIntError,
+    ops::Range,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
 
-pub struct ErrorTracker {
-    inner: Rc<RefCell<Result<()>>>,
+/// What went wrong on a line of puzzle input.
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    InvalidNumber(ParseIntError),
+    WrongValueCount {
+        expected: usize,
+        found: usize,
+    },
+    ValueTooLarge {
+        max: u64,
+    },
+    MissingSeparator {
+        separator: &'static str,
+    },
+    InvalidDigit(char),
+    UnknownCell(char),
+    /// Reported at the end of the input
+    NoGuard,
+    MultipleGuards {
+        first_line: usize,
+        first_column: usize,
+    },
+    /// Reported at the end of the input
+    EvenDiskMap {
+        digits: usize,
+    },
+    RuleCycle,
 }
 
-impl ErrorTracker {
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::InvalidNumber(err) => write!(f, "invalid number: {err}"),
+            ParseErrorKind::WrongValueCount { expected, found } => {
+                write!(f, "expected {expected} values but found {found}")
+            }
+            ParseErrorKind::ValueTooLarge { max } => write!(f, "value must be at most {max}"),
+            ParseErrorKind::MissingSeparator { separator } => {
+                write!(f, "missing separator `{separator}`")
+            }
+            ParseErrorKind::InvalidDigit(c) => {
+                write!(f, "expected a digit but found `{}`", c.escape_debug())
+            }
+            ParseErrorKind::UnknownCell(c) => write!(f, "unknown grid cell `{}`", c.escape_debug()),
+            ParseErrorKind::NoGuard => write!(f, "no guard (`^`) found"),
+            ParseErrorKind::MultipleGuards {
+                first_line,
+                first_column,
+            } => write!(
+                f,
+                "found a second guard, the first is at line {first_line}, column {first_column}"
+            ),
+            ParseErrorKind::EvenDiskMap { digits } => write!(
+                f,
+                "a disk map needs an odd number of digits, to end with a file, but found {digits}"
+            ),
+            ParseErrorKind::RuleCycle => {
+                write!(
+                    f,
+                    "the rules for these pages form a cycle, so they cannot be ordered"
+                )
+            }
+        }
+    }
+}
+
+/// An error in the puzzle input, located by line and column so that it can be
+/// shown with the offending line underlined.
+#[derive(Debug)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// 1-based line number
+    pub line: usize,
+    /// 0-based byte offsets into the line
+    pub columns: Range<usize>,
+    pub source_line: String,
+}
+
+impl ParseError {
+    /// `line_index` is 0-based, as produced by `enumerate`.
+    pub fn new(
+        kind: ParseErrorKind,
+        line_index: usize,
+        columns: Range<usize>,
+        source_line: &str,
+    ) -> Self {
+        ParseError {
+            kind,
+            line: line_index + 1,
+            columns,
+            source_line: source_line.to_string(),
+        }
+    }
+
+    /// The 1-based column, counted in characters, where the error starts.
+    pub fn column(&self) -> usize {
+        let start = self.columns.start.min(self.source_line.len());
+        self.source_line[..start].chars().count() + 1
+    }
+
+    /// Render the error along with the offending line, with the columns it
+    /// refers to underlined by carets.
+    pub fn render(&self) -> String {
+        let line_no = self.line.to_string();
+        let gutter = " ".repeat(line_no.len());
+        let start = self.columns.start.min(self.source_line.len());
+        let end = self.columns.end.clamp(start, self.source_line.len());
+        let width = self.source_line[start..end].chars().count().max(1);
+        format!(
+            "error: {}\n{gutter}--> line {}, column {}\n{gutter} |\n{line_no} | {}\n{gutter} | {}{}",
+            self.kind,
+            self.line,
+            self.column(),
+            self.source_line,
+            " ".repeat(self.column() - 1),
+            "^".repeat(width),
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line,
+            self.column(),
+            self.kind
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// One error collected by an [`ErrorTracker`].
+#[derive(Debug)]
+pub struct TrackedError<E> {
+    /// 0-based position of the failing item, which for the parsers is the line
+    pub index: usize,
+    pub message: String,
+    pub error: E,
+}
+
+/// Every error collected by an [`ErrorTracker`], returned once it is checked.
+#[derive(Debug)]
+pub struct ErrorSummary<E = anyhow::Error> {
+    pub errors: Vec<TrackedError<E>>,
+}
+
+impl<E: fmt::Display> fmt::Display for ErrorSummary<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} error(s)", self.errors.len())?;
+        for tracked in &self.errors {
+            write!(
+                f,
+                "\n  {} (item {}): {}",
+                tracked.message,
+                tracked.index + 1,
+                tracked.error
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for ErrorSummary<E> {}
+
+/// Shared storage for the errors logged through an [`ErrorTracker`] and its
+/// clones.
+pub trait ErrorStore<E>: Default {
+    fn share(&self) -> Self;
+
+    fn push(&self, error: TrackedError<E>);
+
+    /// Returns `None` if the store is still shared.
+    fn into_errors(self) -> Option<Vec<TrackedError<E>>>;
+}
+
+/// Storage for trackers used on a single thread.
+pub type LocalStore<E> = Rc<RefCell<Vec<TrackedError<E>>>>;
+
+/// Storage for trackers whose clones are sent to other threads.
+pub type SyncStore<E> = Arc<Mutex<Vec<TrackedError<E>>>>;
+
+impl<E> ErrorStore<E> for LocalStore<E> {
+    fn share(&self) -> Self {
+        Rc::clone(self)
+    }
+
+    fn push(&self, error: TrackedError<E>) {
+        self.borrow_mut().push(error);
+    }
+
+    fn into_errors(self) -> Option<Vec<TrackedError<E>>> {
+        Rc::into_inner(self).map(RefCell::into_inner)
+    }
+}
+
+impl<E> ErrorStore<E> for SyncStore<E> {
+    fn share(&self) -> Self {
+        Arc::clone(self)
+    }
+
+    fn push(&self, error: TrackedError<E>) {
+        self.lock().unwrap().push(error);
+    }
+
+    fn into_errors(self) -> Option<Vec<TrackedError<E>>> {
+        Arc::into_inner(self).map(|x| x.into_inner().unwrap())
+    }
+}
+
+pub struct ErrorTracker<E = anyhow::Error, S: ErrorStore<E> = LocalStore<E>> {
+    inner: S,
+    _error: PhantomData<fn() -> E>,
+}
+
+/// An [`ErrorTracker`] that is `Send + Sync`, so that clones of it can log
+/// errors from several threads at once.
+pub type SyncErrorTracker<E = anyhow::Error> = ErrorTracker<E, SyncStore<E>>;
+
+impl<E, S: ErrorStore<E>> ErrorTracker<E, S> {
     pub fn new() -> Self {
         ErrorTracker {
-            inner: Rc::new(RefCell::new(Ok(()))),
+            inner: S::default(),
+            _error: PhantomData,
         }
     }
 
     pub fn rc_clone(&self) -> Self {
         ErrorTracker {
-            inner: Rc::clone(&self.inner),
+            inner: self.inner.share(),
+            _error: PhantomData,
         }
     }
 
-    pub fn log_error(&mut self, message: &'static str) {
-        let mut err_ref = self.inner.borrow_mut();
-        if err_ref.is_ok() {
-            *err_ref = Err(anyhow!(message));
-        }
+    pub fn log_error(&mut self, index: usize, message: impl Into<String>, error: E) {
+        self.inner.push(TrackedError {
+            index,
+            message: message.into(),
+            error,
+        });
     }
 
-    pub fn check_status(self) -> Result<()> {
-        Rc::into_inner(self.inner).unwrap().into_inner()
+    /// Assumes every other clone of the tracker has been dropped. The errors
+    /// are reported in order of index, however they were logged.
+    pub fn check_status(self) -> Result<()>
+    where
+        E: fmt::Debug + fmt::Display + Send + Sync + 'static,
+    {
+        let mut errors = self.inner.into_errors().unwrap();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            errors.sort_by_key(|tracked| tracked.index);
+            Err(ErrorSummary { errors }.into())
+        }
     }
 }
 
-impl Default for ErrorTracker {
+impl<E, S: ErrorStore<E>> Default for ErrorTracker<E, S> {
     fn default() -> Self {
         Self::new()
     }
@@ -38,31 +278,104 @@ impl Default for ErrorTracker {
 
 #[allow(clippy::type_complexity)]
 pub trait ScanErrors: Iterator {
-    fn scan_errors<T, E>(
+    /// Yield the `Ok` values, logging every `Err` to `error_ref` along with its
+    /// position and `message` rather than stopping at the first one.
+    fn scan_errors<T, E, S: ErrorStore<E>>(
         self,
-        error_ref: &ErrorTracker,
-        message: &'static str,
-    ) -> Scan<Self, ErrorTracker, impl FnMut(&mut ErrorTracker, Result<T, E>) -> Option<T>>
+        error_ref: &ErrorTracker<E, S>,
+        message: &str,
+    ) -> Flatten<
+        Scan<
+            Self,
+            (ErrorTracker<E, S>, usize),
+            impl FnMut(&mut (ErrorTracker<E, S>, usize), Result<T, E>) -> Option<Option<T>>,
+        >,
+    >
     where
         Self: Sized + Iterator<Item = Result<T, E>>,
     {
+        self.scan_errors_from(error_ref, message, 0)
+    }
+
+    /// Like [`ScanErrors::scan_errors`], but counting positions from
+    /// `first_index`, for when the iterator covers a chunk of a larger input.
+    fn scan_errors_from<T, E, S: ErrorStore<E>>(
+        self,
+        error_ref: &ErrorTracker<E, S>,
+        message: &str,
+        first_index: usize,
+    ) -> Flatten<
+        Scan<
+            Self,
+            (ErrorTracker<E, S>, usize),
+            impl FnMut(&mut (ErrorTracker<E, S>, usize), Result<T, E>) -> Option<Option<T>>,
+        >,
+    >
+    where
+        Self: Sized + Iterator<Item = Result<T, E>>,
+    {
+        let message = message.to_string();
         self.into_iter()
-            .scan(error_ref.rc_clone(), move |err, item| match item {
-                Ok(value) => Some(value),
-                Err(_) => {
-                    err.log_error(message);
-                    None
-                }
-            })
+            .scan(
+                (error_ref.rc_clone(), first_index),
+                move |(err, index), item| {
+                    let value = match item {
+                        Ok(value) => Some(value),
+                        Err(error) => {
+                            err.log_error(*index, message.clone(), error);
+                            None
+                        }
+                    };
+                    *index += 1;
+                    Some(value)
+                },
+            )
+            .flatten()
     }
 }
 
 impl<I, T, E> ScanErrors for I where I: Iterator<Item = Result<T, E>> {}
 
 pub trait Unless: Sized {
-    fn unless(self, error: ErrorTracker) -> Result<Self> {
+    fn unless<E, S: ErrorStore<E>>(self, error: ErrorTracker<E, S>) -> Result<Self>
+    where
+        E: fmt::Debug + fmt::Display + Send + Sync + 'static,
+    {
         error.check_status()?;
         Ok(self)
     }
 }
 impl<T: Sized> Unless for T {}
+
+/// Something suspicious about the input that does not stop it being solved.
+/// Parsers that can notice one take a `warnings` list to add it to.
+#[derive(Clone, Debug)]
+pub struct Warning {
+    /// 1-based line number, if the warning refers to a particular line
+    pub line: Option<usize>,
+    /// 1-based column, if the warning refers to a particular position
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Warning {
+    /// `line_index` and `column_index` are 0-based, as produced by `enumerate`.
+    pub fn at(line_index: usize, column_index: Option<usize>, message: impl Into<String>) -> Self {
+        Warning {
+            line: Some(line_index + 1),
+            column: column_index.map(|x| x + 1),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "line {line}, column {column}: ")?,
+            (Some(line), None) => write!(f, "line {line}: ")?,
+            _ => {}
+        }
+        write!(f, "{}", self.message)
+    }
+}
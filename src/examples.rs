@@ -0,0 +1,184 @@
+/// The worked example from a day's puzzle text, along with its published
+/// answers.
+pub struct Example {
+    pub input: &'static str,
+    pub part1: u64,
+    pub part2: u64,
+}
+
+pub const DAY1: Example = Example {
+    input: "\
+3   4
+4   3
+2   5
+1   3
+3   9
+3   3
+",
+    part1: 11,
+    part2: 31,
+};
+
+pub const DAY2: Example = Example {
+    input: "\
+7 6 4 2 1
+1 2 7 8 9
+9 7 6 2 1
+1 3 2 4 5
+8 6 4 4 1
+1 3 6 7 9
+",
+    part1: 2,
+    part2: 4,
+};
+
+// The puzzle gives a separate example for part 2, but it also contains every
+// instruction from the part 1 example, so it serves for both.
+pub const DAY3: Example = Example {
+    input: "\
+xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))
+",
+    part1: 161,
+    part2: 48,
+};
+
+pub const DAY4: Example = Example {
+    input: "\
+MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX
+",
+    part1: 18,
+    part2: 9,
+};
+
+pub const DAY5: Example = Example {
+    input: "\
+47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47
+",
+    part1: 143,
+    part2: 123,
+};
+
+pub const DAY6: Example = Example {
+    input: "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...
+",
+    part1: 41,
+    part2: 6,
+};
+
+pub const DAY7: Example = Example {
+    input: "\
+190: 10 19
+3267: 81 40 27
+83: 17 5
+156: 15 6
+7290: 6 8 6 15
+161011: 16 10 13
+192: 17 8 14
+21037: 9 7 18 13
+292: 11 6 16 20
+",
+    part1: 3749,
+    part2: 11387,
+};
+
+pub const DAY8: Example = Example {
+    input: "\
+............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............
+",
+    part1: 14,
+    part2: 34,
+};
+
+pub const DAY9: Example = Example {
+    input: "\
+2333133121414131402
+",
+    part1: 1928,
+    part2: 2858,
+};
+
+pub const DAY10: Example = Example {
+    input: "\
+89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732
+",
+    part1: 36,
+    part2: 81,
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{parsers::Source, solutions::DAYS};
+
+    #[test]
+    fn every_day_solves_its_example() {
+        for day in &DAYS {
+            let run = (day.run)(Source::Example(day.example.input), None, None).unwrap();
+            assert_eq!(run.part1, Some(day.example.part1), "{} part 1", day.name);
+            assert_eq!(run.part2, Some(day.example.part2), "{} part 2", day.name);
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use std::io::{self, Write};
+
+use crate::{
+    geometry::{Direction, Position},
+    grid::Grid,
+};
+
+/// A small, fast pseudo-random number generator (SplitMix64). Not suitable for
+/// anything but making test inputs, but the same seed always gives the same
+/// sequence on every platform.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `0..n`. Assumes `n > 0`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        // Multiply-shift reduction, whose bias is negligible for the ranges used
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+
+    /// A value in `low..=high`. Assumes `low <= high`.
+    pub fn between(&mut self, low: u64, high: u64) -> u64 {
+        low + self.below(high - low + 1)
+    }
+
+    /// True with probability `1 / n`.
+    pub fn one_in(&mut self, n: u64) -> bool {
+        self.below(n) == 0
+    }
+
+    pub fn choose<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len() as u64) as usize]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i as u64 + 1) as usize);
+        }
+    }
+}
+
+/// How big an input to make. What each dimension means depends on the day;
+/// anything left unset takes a size similar to the official inputs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Params {
+    /// Lines of input, rows of a grid, or digits of a disk map
+    pub size: Option<usize>,
+    /// Values per line, or columns of a grid
+    pub width: Option<usize>,
+}
+
+/// Write a random well-formed input for one day.
+pub type Generator = fn(&mut Rng, Params, &mut dyn Write) -> io::Result<()>;
+
+fn write_grid(
+    rng: &mut Rng,
+    rows: usize,
+    cols: usize,
+    out: &mut dyn Write,
+    mut cell: impl FnMut(&mut Rng, usize, usize) -> u8,
+) -> io::Result<()> {
+    let mut line = Vec::with_capacity(cols + 1);
+    for i in 0..rows {
+        line.clear();
+        line.extend((0..cols).map(|j| cell(rng, i, j)));
+        line.push(b'\n');
+        out.write_all(&line)?;
+    }
+    Ok(())
+}
+
+/// Pairs of five-digit location IDs, with some IDs repeated so the similarity
+/// score is not zero.
+pub fn day1(rng: &mut Rng, params: Params, out: &mut dyn Write) -> io::Result<()> {
+    let lines = params.size.unwrap_or(1000);
+    let mut left = vec![];
+    for _ in 0..lines {
+        let id = rng.between(10000, 99999);
+        let right = if !left.is_empty() && rng.one_in(4) {
+            rng.choose(&left)
+        } else {
+            rng.between(10000, 99999)
+        };
+        left.push(id);
+        writeln!(out, "{id}   {right}")?;
+    }
+    Ok(())
+}
+
+/// Reports that mostly step up or down gently, with the occasional bad level.
+pub fn day2(rng: &mut Rng, params: Params, out: &mut dyn Write) -> io::Result<()> {
+    for _ in 0..params.size.unwrap_or(1000) {
+        let levels = params
+            .width
+            .unwrap_or_else(|| rng.between(5, 8) as usize)
+            .max(1);
+        let direction = if rng.one_in(2) { 1 } else { -1 };
+        let mut level = rng.between(1, 99) as i64;
+        let mut report = Vec::with_capacity(levels);
+        for _ in 0..levels {
+            report.push(level.to_string());
+            let step = if rng.one_in(10) {
+                rng.between(0, 8) as i64 - 4
+            } else {
+                direction * rng.between(1, 3) as i64
+            };
+            // Bounce off the edges rather than leave the range of an i8
+            level = if (1..=99).contains(&(level + step)) {
+                level + step
+            } else {
+                level - step
+            };
+        }
+        writeln!(out, "{}", report.join(" "))?;
+    }
+    Ok(())
+}
+
+/// Corrupted memory: `mul`, `do` and `don't` instructions, some of them
+/// damaged, between runs of junk.
+pub fn day3(rng: &mut Rng, params: Params, out: &mut dyn Write) -> io::Result<()> {
+    const JUNK: &[u8] = b"!@#$%^&*()[]{}<>?+-_,'~ :;/whyselectfromwhere";
+    let mut line = vec![];
+    for n in 1..=params.size.unwrap_or(700) {
+        for _ in 0..rng.below(12) {
+            line.push(rng.choose(JUNK));
+        }
+        let instruction = match rng.below(20) {
+            0 => "do()".to_string(),
+            1 => "don't()".to_string(),
+            2 => format!("mul({},{}]", rng.between(1, 999), rng.between(1, 999)),
+            3 => format!("mul ( {},{})", rng.between(1, 999), rng.between(1, 999)),
+            4 => format!("mul({},{})", rng.between(1000, 9999), rng.between(1, 999)),
+            _ => format!("mul({},{})", rng.between(1, 999), rng.between(1, 999)),
+        };
+        line.extend_from_slice(instruction.as_bytes());
+        if n % 100 == 0 {
+            line.push(b'\n');
+            out.write_all(&line)?;
+            line.clear();
+        }
+    }
+    line.push(b'\n');
+    out.write_all(&line)
+}
+
+/// A word search made only of the letters of XMAS.
+pub fn day4(rng: &mut Rng, params: Params, out: &mut dyn Write) -> io::Result<()> {
+    let rows = params.size.unwrap_or(140);
+    let cols = params.width.unwrap_or(rows);
+    write_grid(rng, rows, cols, out, |rng, _, _| rng.choose(b"XMAS"))
+}
+
+/// Rules covering every pair of pages, taken from one hidden ordering so that
+/// each update sorts uniquely, followed by updates of which about half are
+/// already in order.
+pub fn day5(rng: &mut Rng, params: Params, out: &mut dyn Write) -> io::Result<()> {
+    let mut order = (10..100).collect::<Vec<u64>>();
+    rng.shuffle(&mut order);
+    let mut rules = vec![];
+    for (n, from) in order.iter().enumerate() {
+        for to in &order[n + 1..] {
+            rules.push((from, to));
+        }
+    }
+    rng.shuffle(&mut rules);
+    for (from, to) in rules {
+        writeln!(out, "{from}|{to}")?;
+    }
+    writeln!(out)?;
+
+    for _ in 0..params.size.unwrap_or(200) {
+        let pages = match params.width {
+            Some(width) => width.clamp(1, order.len()),
+            None => rng.between(2, 11) as usize * 2 + 1,
+        };
+        let mut update = order.clone();
+        rng.shuffle(&mut update);
+        update.truncate(pages);
+        if rng.one_in(2) {
+            update.sort_by_key(|page| order.iter().position(|x| x == page));
+        }
+        let update = update.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        writeln!(out, "{}", update.join(","))?;
+    }
+    Ok(())
+}
+
+/// Follow the guard from `start`, returning an obstacle on their route if they
+/// end up walking in a loop.
+fn find_loop_obstacle(lab: &Grid<u8>, start: Position) -> Option<Position> {
+    let mut seen = Grid::new(lab.rows(), lab.cols(), 0u8);
+    let mut guard = start;
+    let mut heading = Direction::North;
+    loop {
+        if seen[guard] & (1 << heading.index()) != 0 {
+            // Back in a state already seen, so the next obstacle is on the loop
+            while lab[guard + heading] != b'#' {
+                guard += heading;
+            }
+            return Some(guard + heading);
+        }
+        seen[guard] |= 1 << heading.index();
+        let ahead = (guard + heading).to_index().and_then(|x| lab.get(x));
+        match ahead {
+            None => return None,
+            Some(b'#') => heading = heading.turn_clockwise(),
+            Some(_) => guard += heading,
+        }
+    }
+}
+
+/// A lab with scattered obstacles and a guard who eventually walks out of it.
+pub fn day6(rng: &mut Rng, params: Params, out: &mut dyn Write) -> io::Result<()> {
+    let rows = params.size.unwrap_or(130).max(1);
+    let cols = params.width.unwrap_or(rows).max(1);
+    let mut lab = Grid::new(rows, cols, b'.');
+    for pos in lab.positions() {
+        if rng.one_in(10) {
+            lab[pos] = b'#';
+        }
+    }
+    let start = (
+        rng.below(rows as u64) as usize,
+        rng.below(cols as u64) as usize,
+    );
+    lab[start] = b'^';
+    // Each loop is broken by removing one of its obstacles
+    while let Some(obstacle) = find_loop_obstacle(&lab, start.into()) {
+        lab[obstacle] = b'.';
+    }
+    for row in lab.iter_rows() {
+        out.write_all(row)?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Equations of which about half can be made true, keeping every total small
+/// enough that no operator order overflows a `u64`.
+pub fn day7(rng: &mut Rng, params: Params, out: &mut dyn Write) -> io::Result<()> {
+    const LIMIT: u64 = 1 << 48;
+    for _ in 0..params.size.unwrap_or(850) {
+        let operands = params
+            .width
+            .unwrap_or_else(|| rng.between(2, 12) as usize)
+            .max(1);
+        let values = (0..operands)
+            .map(|_| rng.between(1, 999))
+            .collect::<Vec<_>>();
+        let total = if rng.one_in(2) {
+            values[1..].iter().fold(values[0], |total, &x| {
+                let concat = total * 10u64.pow(x.ilog10() + 1) + x;
+                match rng.below(3) {
+                    0 if total * x < LIMIT => total * x,
+                    1 if concat < LIMIT => concat,
+                    _ => total + x,
+                }
+            })
+        } else {
+            rng.between(1, LIMIT)
+        };
+        let values = values.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        writeln!(out, "{total}: {}", values.join(" "))?;
+    }
+    Ok(())
+}
+
+/// A map with a few antennas of each of a handful of frequencies.
+pub fn day8(rng: &mut Rng, params: Params, out: &mut dyn Write) -> io::Result<()> {
+    const FREQUENCIES: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let rows = params.size.unwrap_or(50);
+    let cols = params.width.unwrap_or(rows);
+    let frequencies = &FREQUENCIES[..rng.between(1, FREQUENCIES.len() as u64) as usize];
+    write_grid(rng, rows, cols, out, |rng, _, _| {
+        if rng.one_in(20) {
+            rng.choose(frequencies)
+        } else {
+            b'.'
+        }
+    })
+}
+
+/// A disk map of alternating file and free space lengths, with an odd number
+/// of digits so that it ends with a file.
+pub fn day9(rng: &mut Rng, params: Params, out: &mut dyn Write) -> io::Result<()> {
+    let digits = params.size.unwrap_or(19999) | 1;
+    let mut line = (0..digits)
+        .map(|n| {
+            let digit = if n % 2 == 0 {
+                rng.between(1, 9)
+            } else {
+                rng.between(0, 9)
+            };
+            b'0' + digit as u8
+        })
+        .collect::<Vec<_>>();
+    line.push(b'\n');
+    out.write_all(&line)
+}
+
+/// A topographic map of diagonal ridges, roughened so that trails branch and
+/// dead-end.
+pub fn day10(rng: &mut Rng, params: Params, out: &mut dyn Write) -> io::Result<()> {
+    let rows = params.size.unwrap_or(50);
+    let cols = params.width.unwrap_or(rows);
+    write_grid(rng, rows, cols, out, |rng, i, j| {
+        let height = if rng.one_in(8) {
+            rng.below(10)
+        } else {
+            (i + j + rng.below(2) as usize) as u64 % 10
+        };
+        b'0' + height as u8
+    })
+}
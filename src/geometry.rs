@@ -0,0 +1,186 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A cell of a 2D map as `(row, column)`. Either may be negative, so that
+/// positions just off the edge of a map can still be represented.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub i: i32,
+    pub j: i32,
+}
+
+/// The displacement between two positions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Vector {
+    pub di: i32,
+    pub dj: i32,
+}
+
+/// A compass heading, with north pointing up the map (towards row 0).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Position {
+    pub const fn new(i: i32, j: i32) -> Self {
+        Position { i, j }
+    }
+
+    /// Whether the position lies within a map of the given size.
+    pub fn in_bounds(self, rows: i32, cols: i32) -> bool {
+        (0..rows).contains(&self.i) && (0..cols).contains(&self.j)
+    }
+
+    /// The position as array indices, if neither coordinate is negative.
+    pub fn to_index(self) -> Option<(usize, usize)> {
+        Some((self.i.try_into().ok()?, self.j.try_into().ok()?))
+    }
+}
+
+impl From<(usize, usize)> for Position {
+    fn from((i, j): (usize, usize)) -> Self {
+        Position::new(i as i32, j as i32)
+    }
+}
+
+impl Vector {
+    pub const fn new(di: i32, dj: i32) -> Self {
+        Vector { di, dj }
+    }
+}
+
+impl Direction {
+    /// Clockwise from north.
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub const fn velocity(self) -> Vector {
+        match self {
+            Direction::North => Vector::new(-1, 0),
+            Direction::East => Vector::new(0, 1),
+            Direction::South => Vector::new(1, 0),
+            Direction::West => Vector::new(0, -1),
+        }
+    }
+
+    pub const fn turn_clockwise(self) -> Self {
+        Direction::ALL[(self.index() + 1) % 4]
+    }
+
+    pub const fn turn_counterclockwise(self) -> Self {
+        Direction::ALL[(self.index() + 3) % 4]
+    }
+
+    pub const fn opposite(self) -> Self {
+        Direction::ALL[(self.index() + 2) % 4]
+    }
+
+    /// The position of the direction in [`Direction::ALL`], for use as an
+    /// array index.
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    pub const fn from_index(index: usize) -> Option<Self> {
+        if index < 4 {
+            Some(Direction::ALL[index])
+        } else {
+            None
+        }
+    }
+}
+
+impl From<Direction> for Vector {
+    fn from(direction: Direction) -> Self {
+        direction.velocity()
+    }
+}
+
+impl Add<Vector> for Position {
+    type Output = Position;
+
+    fn add(self, v: Vector) -> Position {
+        Position::new(self.i + v.di, self.j + v.dj)
+    }
+}
+
+impl Add<Direction> for Position {
+    type Output = Position;
+
+    fn add(self, direction: Direction) -> Position {
+        self + direction.velocity()
+    }
+}
+
+impl AddAssign<Vector> for Position {
+    fn add_assign(&mut self, v: Vector) {
+        *self = *self + v;
+    }
+}
+
+impl AddAssign<Direction> for Position {
+    fn add_assign(&mut self, direction: Direction) {
+        *self = *self + direction;
+    }
+}
+
+impl Sub<Vector> for Position {
+    type Output = Position;
+
+    fn sub(self, v: Vector) -> Position {
+        self + -v
+    }
+}
+
+impl SubAssign<Vector> for Position {
+    fn sub_assign(&mut self, v: Vector) {
+        *self = *self - v;
+    }
+}
+
+impl Sub for Position {
+    type Output = Vector;
+
+    fn sub(self, other: Position) -> Vector {
+        Vector::new(self.i - other.i, self.j - other.j)
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Vector {
+        Vector::new(self.di + other.di, self.dj + other.dj)
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, other: Vector) -> Vector {
+        self + -other
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.di, -self.dj)
+    }
+}
+
+impl Mul<i32> for Vector {
+    type Output = Vector;
+
+    fn mul(self, k: i32) -> Vector {
+        Vector::new(self.di * k, self.dj * k)
+    }
+}
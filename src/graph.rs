@@ -0,0 +1,289 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque, hash_map::Entry},
+    hash::Hash,
+};
+
+/// A directed graph given by the successors of each node. Any function from a
+/// node to the nodes it leads to is a graph.
+pub trait Graph<N> {
+    fn neighbors(&self, node: N) -> impl Iterator<Item = N>;
+}
+
+impl<N, F, I> Graph<N> for F
+where
+    F: Fn(N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    fn neighbors(&self, node: N) -> impl Iterator<Item = N> {
+        self(node).into_iter()
+    }
+}
+
+/// A directed graph whose edges have a cost. Any function from a node to pairs
+/// of a successor and the cost of reaching it is a weighted graph.
+pub trait WeightedGraph<N> {
+    fn edges(&self, node: N) -> impl Iterator<Item = (N, u64)>;
+}
+
+impl<N, F, I> WeightedGraph<N> for F
+where
+    F: Fn(N) -> I,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    fn edges(&self, node: N) -> impl Iterator<Item = (N, u64)> {
+        self(node).into_iter()
+    }
+}
+
+/// The number of edges on a shortest path from `start` to every node reachable
+/// from it.
+pub fn bfs<N: Copy + Eq + Hash>(graph: &impl Graph<N>, start: N) -> HashMap<N, usize> {
+    let mut distances = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[&node];
+        for next in graph.neighbors(node) {
+            if let Entry::Vacant(entry) = distances.entry(next) {
+                entry.insert(distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+/// Every node reachable from `start`, in depth-first preorder.
+pub fn dfs<N: Copy + Eq + Hash>(graph: &impl Graph<N>, start: N) -> Vec<N> {
+    let mut visited = HashSet::new();
+    let mut order = vec![];
+    let mut stack = vec![start];
+    while let Some(node) = stack.pop() {
+        if !visited.insert(node) {
+            continue;
+        }
+        order.push(node);
+        // Reversed so that the first neighbour is explored first
+        let neighbors = graph.neighbors(node).collect::<Vec<_>>();
+        stack.extend(neighbors.into_iter().rev().filter(|x| !visited.contains(x)));
+    }
+    order
+}
+
+/// Every node reachable from `start`, including `start` itself.
+pub fn reachable<N: Copy + Eq + Hash>(graph: &impl Graph<N>, start: N) -> HashSet<N> {
+    let mut visited = HashSet::from([start]);
+    let mut stack = vec![start];
+    while let Some(node) = stack.pop() {
+        for next in graph.neighbors(node) {
+            if visited.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    visited
+}
+
+/// The cost of a cheapest path from `start` to every node reachable from it.
+pub fn dijkstra<N: Copy + Ord + Hash>(graph: &impl WeightedGraph<N>, start: N) -> HashMap<N, u64> {
+    let mut costs = HashMap::from([(start, 0)]);
+    let mut heap = BinaryHeap::from([Reverse((0, start))]);
+    while let Some(Reverse((cost, node))) = heap.pop() {
+        if cost > costs[&node] {
+            // A cheaper path to this node has already been handled
+            continue;
+        }
+        for (next, step) in graph.edges(node) {
+            let next_cost = cost + step;
+            if costs.get(&next).is_none_or(|&x| next_cost < x) {
+                costs.insert(next, next_cost);
+                heap.push(Reverse((next_cost, next)));
+            }
+        }
+    }
+    costs
+}
+
+/// Counts the paths from a node to any target node in a DAG, remembering the
+/// count for every node it passes through so that later queries sharing part of
+/// the graph are cheap. A path stops at the first target it reaches.
+pub struct PathCounter<'a, N, G, T> {
+    graph: &'a G,
+    is_target: T,
+    counts: HashMap<N, u64>,
+}
+
+impl<'a, N, G, T> PathCounter<'a, N, G, T>
+where
+    N: Copy + Eq + Hash,
+    G: Graph<N>,
+    T: Fn(N) -> bool,
+{
+    pub fn new(graph: &'a G, is_target: T) -> Self {
+        PathCounter {
+            graph,
+            is_target,
+            counts: HashMap::new(),
+        }
+    }
+
+    /// Assumes that no cycle is reachable from `start`.
+    pub fn count(&mut self, start: N) -> u64 {
+        // Each node is pushed once to expand it and once more, after all of its
+        // neighbours, to add up their counts
+        let mut stack = vec![(start, false)];
+        while let Some((node, expanded)) = stack.pop() {
+            if self.counts.contains_key(&node) {
+                continue;
+            }
+            if (self.is_target)(node) {
+                self.counts.insert(node, 1);
+            } else if expanded {
+                let paths = self.graph.neighbors(node).map(|x| self.counts[&x]).sum();
+                self.counts.insert(node, paths);
+            } else {
+                stack.push((node, true));
+                stack.extend(
+                    self.graph
+                        .neighbors(node)
+                        .filter(|x| !self.counts.contains_key(x))
+                        .map(|x| (x, false)),
+                );
+            }
+        }
+        self.counts[&start]
+    }
+}
+
+/// Order `nodes` so that every edge between two of them points forwards, using
+/// Kahn's algorithm. Edges to nodes outside `nodes` are ignored, and ties are
+/// broken by the order of `nodes`. Returns `None` if the edges between `nodes`
+/// form a cycle.
+pub fn topological_sort<N: Copy + Eq + Hash>(
+    nodes: impl IntoIterator<Item = N>,
+    graph: &impl Graph<N>,
+) -> Option<Vec<N>> {
+    let mut in_degrees = HashMap::new();
+    let nodes = nodes
+        .into_iter()
+        .filter(|&x| in_degrees.insert(x, 0).is_none())
+        .collect::<Vec<_>>();
+    for &node in &nodes {
+        for next in graph.neighbors(node) {
+            if let Some(degree) = in_degrees.get_mut(&next) {
+                *degree += 1;
+            }
+        }
+    }
+
+    let mut queue = nodes
+        .iter()
+        .copied()
+        .filter(|x| in_degrees[x] == 0)
+        .collect::<VecDeque<_>>();
+    let mut order = Vec::with_capacity(nodes.len());
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+        for next in graph.neighbors(node) {
+            if let Some(degree) = in_degrees.get_mut(&next) {
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    (order.len() == nodes.len()).then_some(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0 -> 1 -> 3 -> 4 and 0 -> 2 -> 3, a diamond with a tail.
+    fn diamond(node: u32) -> Vec<u32> {
+        match node {
+            0 => vec![1, 2],
+            1 | 2 => vec![3],
+            3 => vec![4],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn bfs_finds_shortest_distances() {
+        let graph = |node: u32| match node {
+            0 => vec![1, 4],
+            1 => vec![2],
+            2 => vec![3],
+            3 => vec![4],
+            _ => vec![],
+        };
+        let distances = bfs(&graph, 0);
+        assert_eq!(
+            distances,
+            HashMap::from([(0, 0), (1, 1), (2, 2), (3, 3), (4, 1)])
+        );
+    }
+
+    #[test]
+    fn dfs_visits_in_preorder() {
+        assert_eq!(dfs(&diamond, 0), vec![0, 1, 3, 4, 2]);
+        assert_eq!(dfs(&diamond, 2), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn reachable_handles_cycles() {
+        let cycle = |node: u32| [(node + 1) % 3];
+        assert_eq!(reachable(&cycle, 1), HashSet::from([0, 1, 2]));
+        assert_eq!(reachable(&diamond, 1), HashSet::from([1, 3, 4]));
+    }
+
+    #[test]
+    fn dijkstra_prefers_cheaper_longer_paths() {
+        let graph = |node: u32| match node {
+            0 => vec![(1, 10), (2, 1)],
+            2 => vec![(3, 1)],
+            3 => vec![(1, 1)],
+            1 => vec![(4, 5)],
+            _ => vec![],
+        };
+        let costs = dijkstra(&graph, 0);
+        assert_eq!(
+            costs,
+            HashMap::from([(0, 0), (1, 3), (2, 1), (3, 2), (4, 8)])
+        );
+    }
+
+    #[test]
+    fn path_counter_counts_diamond_paths() {
+        let mut counter = PathCounter::new(&diamond, |node| node == 4);
+        assert_eq!(counter.count(0), 2);
+        assert_eq!(counter.count(1), 1);
+        // Paths stop at the first target
+        let mut counter = PathCounter::new(&diamond, |node| node >= 3);
+        assert_eq!(counter.count(0), 2);
+    }
+
+    #[test]
+    fn topological_sort_orders_a_dag() {
+        let order = topological_sort([4, 3, 2, 1, 0], &diamond).unwrap();
+        let position = |node| order.iter().position(|&x| x == node).unwrap();
+        for node in 0..5 {
+            for next in diamond(node) {
+                assert!(position(node) < position(next));
+            }
+        }
+        // Edges to nodes outside the set are ignored, and ties keep the given
+        // order
+        assert_eq!(topological_sort([2, 1, 4], &diamond), Some(vec![2, 1, 4]));
+    }
+
+    #[test]
+    fn topological_sort_rejects_a_cycle() {
+        let cycle = |node: u32| [(node + 1) % 3];
+        assert_eq!(topological_sort([0, 1, 2], &cycle), None);
+        // A cycle outside the nodes being sorted does not matter
+        assert_eq!(topological_sort([0, 1], &cycle), Some(vec![0, 1]));
+    }
+}
@@ -0,0 +1,254 @@
+use anyhow::Result;
+use std::{
+    fmt,
+    iter::{Skip, StepBy},
+    ops::{Index, IndexMut},
+    slice::{ChunksExact, Iter},
+};
+
+use crate::{
+    errors::{ErrorTracker, ParseError, ParseErrorKind, ScanErrors, Unless, Warning},
+    geometry::Position,
+    parsers::{Source, read_lines},
+};
+
+/// Offsets to the four orthogonal neighbours: up, left, right, down.
+pub const NEIGHBOR_OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// Offsets to all eight neighbours, including the diagonals, in reading order.
+pub const NEIGHBOR_OFFSETS_DIAG: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular grid stored row by row in a single `Vec`, indexed by
+/// `(row, column)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(rows: usize, cols: usize, value: T) -> Self {
+        Grid {
+            cells: vec![value; rows * cols],
+            rows,
+            cols,
+        }
+    }
+
+    /// Surround `rows` with a one-cell border of `padding`, so that every
+    /// original cell has all eight neighbours. Rows shorter than the longest
+    /// one are filled out with `padding`.
+    pub fn padded(rows: Vec<Vec<T>>, padding: T) -> Self {
+        let cols = rows.iter().map(|row| row.len()).max().unwrap_or(0) + 2;
+        let mut cells = Vec::with_capacity((rows.len() + 2) * cols);
+        cells.resize(cols, padding.clone());
+        for row in &rows {
+            cells.push(padding.clone());
+            cells.extend_from_slice(row);
+            cells.resize(cells.len() + cols - 1 - row.len(), padding.clone());
+        }
+        cells.resize(cells.len() + cols, padding);
+        Grid {
+            cells,
+            rows: rows.len() + 2,
+            cols,
+        }
+    }
+
+    /// Read a padded grid with one cell per character. `parse_cell` is given
+    /// the 0-based line and column of each character in the input, and every
+    /// cell it rejects is reported. A ragged row is only added to `warnings`,
+    /// and filled out as in [`Grid::padded`].
+    pub fn read_padded<'a>(
+        source: impl Into<Source<'a>>,
+        padding: T,
+        message: &str,
+        warnings: &mut Vec<Warning>,
+        mut parse_cell: impl FnMut(usize, usize, char) -> Result<T, ParseErrorKind>,
+    ) -> Result<Self> {
+        let errors: ErrorTracker = ErrorTracker::new();
+        let mut first_width = None;
+        let rows = read_lines(source)?
+            .enumerate()
+            .map(|(i, line)| {
+                let line = line?;
+                let row = line
+                    .char_indices()
+                    .enumerate()
+                    .map(|(j, (start, c))| {
+                        parse_cell(i, j, c).map_err(|kind| {
+                            ParseError::new(kind, i, start..start + c.len_utf8(), &line)
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let width = *first_width.get_or_insert(row.len());
+                if row.len() != width {
+                    warnings.push(Warning::at(
+                        i,
+                        None,
+                        format!("row has {} cells but the first row has {width}", row.len()),
+                    ));
+                }
+                Ok(row)
+            })
+            .scan_errors(&errors, message)
+            .collect::<Vec<_>>();
+        Ok(Grid::padded(rows.unless(errors)?, padding))
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, (i, j): (usize, usize)) -> Option<&T> {
+        (i < self.rows && j < self.cols).then(|| &self.cells[i * self.cols + j])
+    }
+
+    pub fn get_mut(&mut self, (i, j): (usize, usize)) -> Option<&mut T> {
+        (i < self.rows && j < self.cols).then(|| &mut self.cells[i * self.cols + j])
+    }
+
+    /// # Safety
+    ///
+    /// `i` must be less than `rows()` and `j` less than `cols()`.
+    pub unsafe fn get_unchecked(&self, (i, j): (usize, usize)) -> &T {
+        // SAFETY: the caller guarantees the index is within the grid
+        unsafe { self.cells.get_unchecked(i * self.cols + j) }
+    }
+
+    /// # Safety
+    ///
+    /// `i` must be less than `rows()` and `j` less than `cols()`.
+    pub unsafe fn get_unchecked_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        // SAFETY: the caller guarantees the index is within the grid
+        unsafe { self.cells.get_unchecked_mut(i * self.cols + j) }
+    }
+
+    /// The position one `offset` away from `(i, j)`, if it is within the grid.
+    pub fn step(&self, (i, j): (usize, usize), (di, dj): (isize, isize)) -> Option<(usize, usize)> {
+        let i = i.checked_add_signed(di).filter(|&i| i < self.rows)?;
+        let j = j.checked_add_signed(dj).filter(|&j| j < self.cols)?;
+        Some((i, j))
+    }
+
+    /// The orthogonal neighbours of `(i, j)` that are within the grid.
+    pub fn neighbors(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBOR_OFFSETS
+            .into_iter()
+            .filter_map(move |offset| self.step(pos, offset))
+    }
+
+    /// The orthogonal and diagonal neighbours of `(i, j)` that are within the
+    /// grid.
+    pub fn neighbors_diag(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBOR_OFFSETS_DIAG
+            .into_iter()
+            .filter_map(move |offset| self.step(pos, offset))
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.cells[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn iter_rows(&self) -> ChunksExact<'_, T> {
+        self.cells.chunks_exact(self.cols.max(1))
+    }
+
+    pub fn column(&self, j: usize) -> StepBy<Skip<Iter<'_, T>>> {
+        assert!(
+            j < self.cols,
+            "column {j} out of bounds for {} columns",
+            self.cols
+        );
+        self.cells.iter().skip(j).step_by(self.cols)
+    }
+
+    pub fn iter_columns(&self) -> impl Iterator<Item = StepBy<Skip<Iter<'_, T>>>> {
+        (0..self.cols).map(|j| self.column(j))
+    }
+
+    /// Every position in reading order.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let cols = self.cols;
+        (0..self.rows).flat_map(move |i| (0..cols).map(move |j| (i, j)))
+    }
+
+    /// Every position off the border in reading order, i.e. the original cells
+    /// of a padded grid.
+    pub fn inner_positions(&self) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let cols = self.cols;
+        (1..self.rows.saturating_sub(1))
+            .flat_map(move |i| (1..cols.saturating_sub(1)).map(move |j| (i, j)))
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        assert!(
+            j < self.cols,
+            "column {j} out of bounds for {} columns",
+            self.cols
+        );
+        &self.cells[i * self.cols + j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        assert!(
+            j < self.cols,
+            "column {j} out of bounds for {} columns",
+            self.cols
+        );
+        &mut self.cells[i * self.cols + j]
+    }
+}
+
+/// Panics if either coordinate is negative.
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Position) -> &T {
+        &self[pos.to_index().expect("position off the grid")]
+    }
+}
+
+/// Panics if either coordinate is negative.
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, pos: Position) -> &mut T {
+        &mut self[pos.to_index().expect("position off the grid")]
+    }
+}
+
+/// Each row on its own line, with the cells written one after another.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.iter_rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+        }
+        Ok(())
+    }
+}
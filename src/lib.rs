@@ -2,22 +2,40 @@
 
 use std::{
     cmp::Ordering,
-    collections::{BTreeSet, HashMap, HashSet},
+    collections::{HashMap, HashSet},
+    fmt,
 };
 
+use crate::{
+    geometry::{Direction, Position},
+    graph::{PathCounter, reachable, topological_sort},
+    grid::{Grid, NEIGHBOR_OFFSETS_DIAG},
+};
+
+pub mod bench;
 pub mod errors;
+pub mod examples;
+pub mod generate;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
 pub mod parsers;
-
-pub fn day1(mut list1: Vec<u32>, mut list2: Vec<u32>) -> (u64, u64) {
-    list1.sort();
-    list2.sort();
-
-    let difference = list1
+pub mod report;
+pub mod solutions;
+pub mod verify;
+pub mod writers;
+
+/// Assumes both lists are sorted.
+fn total_distance(list1: &[u32], list2: &[u32]) -> u64 {
+    list1
         .iter()
-        .zip(&list2)
+        .zip(list2)
         .map(|(x, y)| x.abs_diff(*y) as u64)
-        .sum();
+        .sum()
+}
 
+/// Assumes both lists are sorted.
+fn similarity_score(list1: &[u32], list2: &[u32]) -> u64 {
     let mut score = 0;
 
     let mut list1_iter = list1.iter();
@@ -27,10 +45,10 @@ pub fn day1(mut list1: Vec<u32>, mut list2: Vec<u32>) -> (u64, u64) {
     let list2_entry = list2_iter.next();
 
     let Some(mut list1_val) = list1_entry else {
-        return (difference, score);
+        return score;
     };
     let Some(mut list2_val) = list2_entry else {
-        return (difference, score);
+        return score;
     };
 
     loop {
@@ -39,7 +57,7 @@ pub fn day1(mut list1: Vec<u32>, mut list2: Vec<u32>) -> (u64, u64) {
                 if let Some(next_list1_val) = list1_iter.next() {
                     list1_val = next_list1_val;
                 } else {
-                    return (difference, score);
+                    return score;
                 }
             }
             Ordering::Equal => {
@@ -73,170 +91,316 @@ pub fn day1(mut list1: Vec<u32>, mut list2: Vec<u32>) -> (u64, u64) {
                     list1_val = next_list1_val;
                     list2_val = next_list2_val;
                 } else {
-                    return (difference, score);
+                    return score;
                 }
             }
             Ordering::Greater => {
                 if let Some(next_list2_val) = list2_iter.next() {
                     list2_val = next_list2_val;
                 } else {
-                    return (difference, score);
+                    return score;
                 }
             }
         }
     }
 }
 
-pub fn valid_report_diff(diff: i8) -> bool {
-    (1..=3).contains(&diff)
+pub fn day1_part1(mut list1: Vec<u32>, mut list2: Vec<u32>) -> u64 {
+    list1.sort();
+    list2.sort();
+    total_distance(&list1, &list2)
 }
 
-pub enum ReportResult {
-    NoMismatch,       // (val --> next_val is safe)
-    OneMismatch,      // A mismatch occurred, but no other mismatches occur in the window
-    MultipleMismatch, // An unresolvable mismatch occurred
+pub fn day1_part2(mut list1: Vec<u32>, mut list2: Vec<u32>) -> u64 {
+    list1.sort();
+    list2.sort();
+    similarity_score(&list1, &list2)
 }
 
-// Assumes (prev_val --> val) is safe
-pub fn process_padded_window_increasing(
-    prev_val: i8,
-    val: i8,
-    next_val: i8,
-    next_next_val: i8,
-) -> ReportResult {
-    if !valid_report_diff(next_val - val) {
-        if valid_report_diff(next_next_val - val)
-            || (valid_report_diff(next_val - prev_val)
-                && valid_report_diff(next_next_val - next_val))
-        {
-            ReportResult::OneMismatch
+pub fn day1(list1: Vec<u32>, list2: Vec<u32>) -> (u64, u64) {
+    (
+        day1_part1(list1.clone(), list2.clone()),
+        day1_part2(list1, list2),
+    )
+}
+
+/// A signed integer type that the levels of a report can be held in. Every
+/// difference between levels is checked, so no width can overflow.
+pub trait Level: Copy + Ord + TryFrom<i64> + fmt::Debug + fmt::Display {
+    const MAX: Self;
+
+    fn checked_sub(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_level {
+    ($($t:ty),*) => {
+        $(
+            impl Level for $t {
+                const MAX: Self = <$t>::MAX;
+
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_level!(i8, i16, i32, i64);
+
+/// Every report of an input, held in the narrowest level type that fits all
+/// of their levels.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Reports {
+    I8(Vec<Vec<i8>>),
+    I16(Vec<Vec<i16>>),
+    I32(Vec<Vec<i32>>),
+    I64(Vec<Vec<i64>>),
+}
+
+/// The sizes of step between neighbouring levels that keep a report safe, in
+/// the direction the report is heading. A `min` of 0 lets a report hold level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepRange {
+    min: i64,
+    max: i64,
+}
+
+impl StepRange {
+    /// Returns `None` if the range is empty or `min` is negative.
+    pub const fn new(min: i64, max: i64) -> Option<Self> {
+        if 0 <= min && min <= max {
+            Some(StepRange { min, max })
         } else {
-            ReportResult::MultipleMismatch
+            None
         }
-    } else {
-        ReportResult::NoMismatch
+    }
+
+    pub const fn min(self) -> i64 {
+        self.min
+    }
+
+    pub const fn max(self) -> i64 {
+        self.max
+    }
+
+    /// A bound past the largest `T` is never negative, so no step reaches
+    /// such a `min`, and every step is within such a `max`.
+    pub fn contains<T: Level>(self, step: T) -> bool {
+        let Ok(min) = T::try_from(self.min) else {
+            return false;
+        };
+        let max = T::try_from(self.max).unwrap_or(T::MAX);
+        (min..=max).contains(&step)
     }
 }
 
-// Assumes (prev_val --> val) is safe
-pub fn process_padded_window_decreasing(
-    prev_val: i8,
-    val: i8,
-    next_val: i8,
-    next_next_val: i8,
-) -> ReportResult {
-    if !valid_report_diff(val - next_val) {
-        if valid_report_diff(val - next_next_val)
-            || (valid_report_diff(prev_val - next_val)
-                && valid_report_diff(next_val - next_next_val))
-        {
-            ReportResult::OneMismatch
-        } else {
-            ReportResult::MultipleMismatch
-        }
-    } else {
-        ReportResult::NoMismatch
+/// The puzzle's range of 1 to 3.
+impl Default for StepRange {
+    fn default() -> Self {
+        StepRange { min: 1, max: 3 }
     }
 }
 
-pub fn check_report_safety_increasing(report: &[i8]) -> bool {
-    for [val, next_val] in report.array_windows() {
-        if !valid_report_diff(next_val - val) {
+pub fn valid_report_diff<T: Level>(diff: T, steps: StepRange) -> bool {
+    steps.contains(diff)
+}
+
+pub fn check_report_safety_increasing<T: Level>(report: &[T], steps: StepRange) -> bool {
+    for &[val, next_val] in report.array_windows() {
+        if !steps_safely(val, next_val, steps, Trend::Increasing) {
             return false;
         }
     }
     true
 }
 
-pub fn check_report_safety_decreasing(report: &[i8]) -> bool {
-    for [val, next_val] in report.array_windows() {
-        if !valid_report_diff(val - next_val) {
+pub fn check_report_safety_decreasing<T: Level>(report: &[T], steps: StepRange) -> bool {
+    for &[val, next_val] in report.array_windows() {
+        if !steps_safely(val, next_val, steps, Trend::Decreasing) {
             return false;
         }
     }
     true
 }
 
-// Assumes report.len() >= 4
-pub fn check_report_safety_increasing_one_mismatch(report: &[i8]) -> bool {
-    if !valid_report_diff(report[1] - report[0]) {
-        if valid_report_diff(report[2] - report[1]) || valid_report_diff(report[2] - report[0]) {
-            return check_report_safety_increasing(&report[2..]);
-        } else {
-            return false;
-        }
+pub fn check_report_safety<T: Level>(report: &[T], steps: StepRange) -> bool {
+    if report.len() < 2 {
+        return true;
     }
-    for (i, [prev_val, val, next_val, next_next_val]) in report.array_windows().enumerate() {
-        match process_padded_window_increasing(*prev_val, *val, *next_val, *next_next_val) {
-            ReportResult::NoMismatch => continue,
-            ReportResult::OneMismatch => return check_report_safety_increasing(&report[i + 3..]),
-            ReportResult::MultipleMismatch => return false,
+    match report[0].cmp(&report[1]) {
+        Ordering::Less => check_report_safety_increasing(report, steps),
+        Ordering::Greater => check_report_safety_decreasing(report, steps),
+        // Only possible to be safe if steps of 0 are, so the direction is
+        // not settled yet
+        Ordering::Equal => {
+            check_report_safety_increasing(report, steps)
+                || check_report_safety_decreasing(report, steps)
         }
     }
-    true
 }
 
-pub fn check_report_safety_decreasing_one_mismatch(report: &[i8]) -> bool {
-    if !valid_report_diff(report[0] - report[1]) {
-        if valid_report_diff(report[1] - report[2]) || valid_report_diff(report[0] - report[2]) {
-            return check_report_safety_decreasing(&report[2..]);
-        } else {
-            return false;
+/// Which way the levels of a report are heading.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trend {
+    Increasing,
+    Decreasing,
+}
+
+impl Trend {
+    pub const ALL: [Trend; 2] = [Trend::Increasing, Trend::Decreasing];
+}
+
+/// Whether going from level `from` to level `to` is a safe step in `trend`. A
+/// difference too large for the level type is never safe.
+fn steps_safely<T: Level>(from: T, to: T, steps: StepRange, trend: Trend) -> bool {
+    let diff = match trend {
+        Trend::Increasing => to.checked_sub(from),
+        Trend::Decreasing => from.checked_sub(to),
+    };
+    diff.is_some_and(|x| valid_report_diff(x, steps))
+}
+
+/// The indices of the fewest levels to remove from `report` so that each kept
+/// level steps safely in `trend` from the one before. Returns `None` if more
+/// than `k` would have to go.
+fn fewest_removals<T: Level>(
+    report: &[T],
+    k: usize,
+    steps: StepRange,
+    trend: Trend,
+) -> Option<Vec<usize>> {
+    let n = report.len();
+    // kept[j] is the most levels that can be kept up to and including level j,
+    // when level j is kept, along with the kept level before it
+    let mut kept: Vec<Option<(usize, Option<usize>)>> = vec![None; n];
+    for j in 0..n {
+        if j <= k {
+            // Every level before this one is removed
+            kept[j] = Some((1, None));
         }
-    }
-    for (i, [prev_val, val, next_val, next_next_val]) in report.array_windows().enumerate() {
-        match process_padded_window_decreasing(*prev_val, *val, *next_val, *next_next_val) {
-            ReportResult::NoMismatch => continue,
-            ReportResult::OneMismatch => return check_report_safety_decreasing(&report[i + 3..]),
-            ReportResult::MultipleMismatch => return false,
+        for i in j.saturating_sub(k + 1)..j {
+            if let Some((before, _)) = kept[i]
+                && steps_safely(report[i], report[j], steps, trend)
+                && kept[j].is_none_or(|(x, _)| before + 1 > x)
+            {
+                kept[j] = Some((before + 1, Some(i)));
+            }
         }
     }
-    true
+    // Every level after the last kept one is removed
+    let (last, count) = (n.saturating_sub(k + 1)..n)
+        .filter_map(|j| Some((j, kept[j]?.0)))
+        .max_by_key(|&(_, count)| count)?;
+    if n - count > k {
+        return None;
+    }
+
+    let mut removed = vec![true; n];
+    let mut level = Some(last);
+    while let Some(j) = level {
+        removed[j] = false;
+        level = kept[j].and_then(|(_, before)| before);
+    }
+    Some((0..n).filter(|&j| removed[j]).collect())
 }
 
-pub fn check_report_safety(report: &[i8]) -> bool {
-    if report.len() < 2 {
-        return true;
+/// Whether the report is safe after removing at most `k` levels. Takes
+/// O(n * k) time for a report of n levels.
+pub fn check_report_safety_k_mismatch<T: Level>(report: &[T], k: usize, steps: StepRange) -> bool {
+    // Removing more levels than there are is no different, and keeps k + 1
+    // from overflowing
+    let k = k.min(report.len());
+    report.len() <= k + 1
+        || Trend::ALL
+            .into_iter()
+            .any(|trend| fewest_removals(report, k, steps, trend).is_some())
+}
+
+/// How a report fares, for telling exactly which reports an answer counted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReportSafety {
+    Safe,
+    /// Safe once the levels at these indices are removed
+    SafeWithout(Vec<usize>),
+    /// Unsafe even after removing levels. `index` is the first of the first
+    /// pair of neighbouring levels that does not step safely in `trend`.
+    Unsafe {
+        index: usize,
+        trend: Trend,
+    },
+}
+
+/// Classify the report, allowing at most `k` levels to be removed. If it needs
+/// levels removed, the fewest are chosen.
+pub fn explain_report<T: Level>(report: &[T], k: usize, steps: StepRange) -> ReportSafety {
+    let k = k.min(report.len());
+    if check_report_safety(report, steps) {
+        return ReportSafety::Safe;
     }
-    if report[0] < report[1] {
-        check_report_safety_increasing(report)
-    } else {
-        check_report_safety_decreasing(report)
+    let removals = Trend::ALL
+        .into_iter()
+        .filter_map(|trend| fewest_removals(report, k, steps, trend))
+        .min_by_key(|x| x.len());
+    if let Some(removed) = removals {
+        return ReportSafety::SafeWithout(removed);
     }
-}
 
-pub fn check_report_safety_one_mismatch(report: &[i8]) -> bool {
-    match report.len() {
-        0..=2 => true,
-        3 => valid_report_diff(report[0] - report[1]) || valid_report_diff(report[1] - report[2]),
-        _ => {
-            let num_positive = report[0..4]
-                .array_windows::<2>()
-                .filter(|[x, y]| x < y)
-                .count();
-            if num_positive < 2 {
-                check_report_safety_decreasing_one_mismatch(report)
-            } else {
-                check_report_safety_increasing_one_mismatch(report)
-            }
+    // Blame the trend the report keeps to for longest
+    let first_bad = |trend| {
+        report
+            .array_windows()
+            .position(|&[from, to]| !steps_safely(from, to, steps, trend))
+            .unwrap_or(report.len())
+    };
+    let increasing = first_bad(Trend::Increasing);
+    let decreasing = first_bad(Trend::Decreasing);
+    if decreasing > increasing || (decreasing == increasing && report[0] > report[1]) {
+        ReportSafety::Unsafe {
+            index: decreasing,
+            trend: Trend::Decreasing,
+        }
+    } else {
+        ReportSafety::Unsafe {
+            index: increasing,
+            trend: Trend::Increasing,
         }
     }
 }
 
-pub fn day2(reports: Vec<Vec<i8>>) -> (usize, usize) {
-    let count_no_mismatch = reports.iter().filter(|x| check_report_safety(x)).count();
-    let count_one_mismatch = reports
+pub fn check_report_safety_one_mismatch<T: Level>(report: &[T], steps: StepRange) -> bool {
+    check_report_safety_k_mismatch(report, 1, steps)
+}
+
+pub fn day2_part1<T: Level>(reports: &[Vec<T>], steps: StepRange) -> usize {
+    reports
         .iter()
-        .filter(|x| check_report_safety_one_mismatch(x))
-        .count();
-    (count_no_mismatch, count_one_mismatch)
+        .filter(|x| check_report_safety(x, steps))
+        .count()
+}
+
+pub fn day2_part2<T: Level>(reports: &[Vec<T>], steps: StepRange) -> usize {
+    day2_tolerant(reports, 1, steps)
+}
+
+/// The number of reports that are safe after removing at most `k` levels.
+pub fn day2_tolerant<T: Level>(reports: &[Vec<T>], k: usize, steps: StepRange) -> usize {
+    reports
+        .iter()
+        .filter(|x| check_report_safety_k_mismatch(x, k, steps))
+        .count()
+}
+
+pub fn day2<T: Level>(reports: Vec<Vec<T>>) -> (usize, usize) {
+    let steps = StepRange::default();
+    (day2_part1(&reports, steps), day2_part2(&reports, steps))
 }
 
 //          11  111  1111
 // 12345678901  234  5678
 // mul(###,###) do() n't()
 
-pub fn day3(input: &[u8], conditionals_on: bool) -> u32 {
+pub fn day3(input: &[u8], conditionals_on: bool) -> u64 {
     let mut sum = 0;
     let mut val1 = 0;
     let mut val2 = 0;
@@ -276,7 +440,7 @@ pub fn day3(input: &[u8], conditionals_on: bool) -> u32 {
                 }
             }
             (9..=11, b')', true) => {
-                sum += val1 * val2;
+                sum += u64::from(val1 * val2);
                 0
             }
             (s @ 9..=10, c, true) => {
@@ -308,41 +472,34 @@ pub fn day3(input: &[u8], conditionals_on: bool) -> u32 {
     sum
 }
 
-const NEIGHBOR_OFFSETS: [(i32, i32); 8] = [
-    (-1, -1),
-    (-1, 0),
-    (-1, 1),
-    (0, -1),
-    (0, 1),
-    (1, -1),
-    (1, 0),
-    (1, 1),
-];
+pub fn day3_part1(input: &[u8]) -> u64 {
+    day3(input, false)
+}
+
+pub fn day3_part2(input: &[u8]) -> u64 {
+    day3(input, true)
+}
 
-/// Return whether XMAS is present in grid, starting at (i, j) and in direction
-/// (offset_i, offset_j). Assumes we have already verified grid[i][j] = b'X' and
+/// Return whether XMAS is present in grid, starting at `pos` and in direction
+/// `offset`. Assumes we have already verified the grid has b'X' at `pos` and
 /// that grid is padded.
-fn check_for_word(grid: &[Vec<u8>], i: usize, j: usize, offset_i: i32, offset_j: i32) -> bool {
-    let mut i = i as i32 + offset_i;
-    let mut j = j as i32 + offset_j;
+fn check_for_word(grid: &Grid<u8>, mut pos: (usize, usize), offset: (isize, isize)) -> bool {
     for c in b"MAS" {
-        if grid[i as usize][j as usize] == *c {
-            i += offset_i;
-            j += offset_j;
-        } else {
-            return false;
+        match grid.step(pos, offset) {
+            Some(next) if grid[next] == *c => pos = next,
+            _ => return false,
         }
     }
     true
 }
 
-/// Return the number of times XMAS appears in the grid starting at (i, j).
+/// Return the number of times XMAS appears in the grid starting at `pos`.
 /// Assumes that grid is padded.
-fn count_words_at_spot(grid: &[Vec<u8>], i: usize, j: usize) -> u64 {
+fn count_words_at_spot(grid: &Grid<u8>, pos: (usize, usize)) -> u64 {
     let mut count = 0;
-    if grid[i][j] == b'X' {
-        for (offset_i, offset_j) in NEIGHBOR_OFFSETS {
-            if check_for_word(grid, i, j, offset_i, offset_j) {
+    if grid[pos] == b'X' {
+        for offset in NEIGHBOR_OFFSETS_DIAG {
+            if check_for_word(grid, pos, offset) {
                 count += 1;
             }
         }
@@ -351,118 +508,95 @@ fn count_words_at_spot(grid: &[Vec<u8>], i: usize, j: usize) -> u64 {
 }
 
 /// Assumes that grid is padded.
-fn count_words(grid: &[Vec<u8>]) -> u64 {
-    let mut count = 0;
-    let rows = grid.len();
-    let cols = grid[0].len();
-    for i in 1..rows - 1 {
-        for j in 1..cols - 1 {
-            count += count_words_at_spot(grid, i, j);
-        }
-    }
-    count
+fn count_words(grid: &Grid<u8>) -> u64 {
+    grid.inner_positions()
+        .map(|pos| count_words_at_spot(grid, pos))
+        .sum()
 }
 
 /// Assumes that grid is padded.
-fn check_for_cross(grid: &[Vec<u8>], i: usize, j: usize) -> bool {
-    grid[i][j] == b'A'
-        && (grid[i - 1][j - 1] == b'M' && grid[i + 1][j + 1] == b'S'
-            || grid[i - 1][j - 1] == b'S' && grid[i + 1][j + 1] == b'M')
-        && (grid[i - 1][j + 1] == b'M' && grid[i + 1][j - 1] == b'S'
-            || grid[i - 1][j + 1] == b'S' && grid[i + 1][j - 1] == b'M')
+fn check_for_cross(grid: &Grid<u8>, (i, j): (usize, usize)) -> bool {
+    grid[(i, j)] == b'A'
+        && (grid[(i - 1, j - 1)] == b'M' && grid[(i + 1, j + 1)] == b'S'
+            || grid[(i - 1, j - 1)] == b'S' && grid[(i + 1, j + 1)] == b'M')
+        && (grid[(i - 1, j + 1)] == b'M' && grid[(i + 1, j - 1)] == b'S'
+            || grid[(i - 1, j + 1)] == b'S' && grid[(i + 1, j - 1)] == b'M')
 }
 
 /// Assumes that grid is padded.
-fn count_crosses(grid: &[Vec<u8>]) -> u64 {
-    let mut count = 0;
-    let rows = grid.len();
-    let cols = grid[0].len();
-    for i in 1..rows - 1 {
-        for j in 1..cols - 1 {
-            if check_for_cross(grid, i, j) {
-                count += 1;
-            }
-        }
-    }
-    count
+fn count_crosses(grid: &Grid<u8>) -> u64 {
+    grid.inner_positions()
+        .filter(|&pos| check_for_cross(grid, pos))
+        .count() as u64
 }
 
-pub fn day4(mut grid: Vec<Vec<u8>>) -> (u64, u64) {
-    let cols = grid[0].len();
-    for row in grid.iter_mut() {
-        row.splice(0..0, [b'.']);
-        row.extend_from_slice(b".");
-    }
-    grid.splice(0..0, [vec![b'.'; cols + 2]]);
-    grid.extend([vec![b'.'; cols + 2]]);
+/// Assumes that grid is padded.
+pub fn day4_part1(grid: &Grid<u8>) -> u64 {
+    count_words(grid)
+}
 
-    (count_words(&grid), count_crosses(&grid))
+/// Assumes that grid is padded.
+pub fn day4_part2(grid: &Grid<u8>) -> u64 {
+    count_crosses(grid)
 }
 
-struct PageSorter<'a> {
-    rules_matrix: &'a [[bool; 100]; 100],
-    start_pages: BTreeSet<usize>,
-    pages_remaining: HashSet<usize>,
+/// Assumes that grid is padded.
+pub fn day4(grid: &Grid<u8>) -> (u64, u64) {
+    (day4_part1(grid), day4_part2(grid))
 }
 
-impl<'a> PageSorter<'a> {
-    fn new(rules_matrix: &'a [[bool; 100]; 100], update: &[usize]) -> Self {
-        Self {
-            rules_matrix,
-            start_pages: BTreeSet::new(),
-            pages_remaining: HashSet::from_iter(update.iter().copied()),
-        }
+fn build_rules_matrix(rules: &[(usize, usize)]) -> [[bool; 100]; 100] {
+    let mut rules_matrix = [[false; 100]; 100];
+    for &(from, to) in rules {
+        rules_matrix[from][to] = true;
     }
+    rules_matrix
 }
 
-impl Iterator for PageSorter<'_> {
-    type Item = usize;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(page) = self.start_pages.pop_first() {
-            self.pages_remaining.remove(&page);
-            Some(page)
-        } else {
-            let pages = self.pages_remaining.iter().copied().collect::<Vec<_>>();
-            for page in &pages {
-                let is_start_node = pages.iter().all(|from| !self.rules_matrix[*from][*page]);
-                if is_start_node {
-                    self.pages_remaining.remove(page);
-                    self.start_pages.insert(*page);
-                }
+fn is_correctly_ordered(rules_matrix: &[[bool; 100]; 100], update: &[usize]) -> bool {
+    for (i, from) in update.iter().enumerate() {
+        for to in update.iter().skip(i + 1) {
+            // Check if it violates the rule
+            if rules_matrix[*to][*from] {
+                return false;
             }
-            self.start_pages.pop_first()
         }
     }
+    true
 }
 
-pub fn day5(rules: Vec<(usize, usize)>, updates: Vec<Vec<usize>>) -> (usize, usize) {
-    let mut correct_middle = 0;
-    let mut sorted_middle = 0;
-    let mut rules_matrix = [[false; 100]; 100];
-    for (from, to) in rules {
-        rules_matrix[from][to] = true;
-    }
-    'a: for update in updates {
-        for (i, from) in update.iter().enumerate() {
-            for to in update.iter().skip(i + 1) {
-                // Check if it violates the rule
-                if rules_matrix[*to][*from] {
-                    sorted_middle += PageSorter::new(&rules_matrix, &update)
-                        .nth(update.len() / 2)
-                        .unwrap();
+/// Assumes that the rules between the pages of `update` have no cycle, which
+/// `day5_parser` checks.
+fn sorted_middle(rules_matrix: &[[bool; 100]; 100], update: &[usize]) -> usize {
+    let rules = |from: usize| (0..100).filter(move |&to| rules_matrix[from][to]);
+    let sorted = topological_sort(update.iter().copied(), &rules)
+        .expect("rules between the pages of an update form a cycle");
+    sorted[sorted.len() / 2]
+}
 
-                    continue 'a;
-                }
-            }
-        }
-        correct_middle += update[update.len() / 2];
-    }
+pub fn day5_part1(rules: &[(usize, usize)], updates: &[Vec<usize>]) -> usize {
+    let rules_matrix = build_rules_matrix(rules);
+    updates
+        .iter()
+        .filter(|update| is_correctly_ordered(&rules_matrix, update))
+        .map(|update| update[update.len() / 2])
+        .sum()
+}
 
-    (correct_middle, sorted_middle)
+pub fn day5_part2(rules: &[(usize, usize)], updates: &[Vec<usize>]) -> usize {
+    let rules_matrix = build_rules_matrix(rules);
+    updates
+        .iter()
+        .filter(|update| !is_correctly_ordered(&rules_matrix, update))
+        .map(|update| sorted_middle(&rules_matrix, update))
+        .sum()
 }
 
-#[derive(Clone)]
+pub fn day5(rules: Vec<(usize, usize)>, updates: Vec<Vec<usize>>) -> (usize, usize) {
+    (day5_part1(&rules, &updates), day5_part2(&rules, &updates))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum LabEntry {
     OutOfBounds,
     Vacant,
@@ -474,98 +608,94 @@ pub enum LabEntry {
     },
 }
 
-#[allow(clippy::too_many_arguments)]
 pub fn simulate_obstacle(
-    grid: &mut [Vec<LabEntry>],
-    mut i: i32,
-    mut j: i32,
-    mut vi: i32,
-    mut vj: i32,
-    mut dir: usize,
+    grid: &mut Grid<LabEntry>,
+    mut guard: Position,
+    mut heading: Direction,
     simul_count: &mut usize,
 ) -> bool {
-    let obj_i = (i + vi) as usize;
-    let obj_j = (j + vj) as usize;
-    let original = std::mem::replace(&mut grid[obj_i][obj_j], LabEntry::Obstacle {
+    let obstacle = guard + heading;
+    let original = std::mem::replace(&mut grid[obstacle], LabEntry::Obstacle {
         hits: [false; 4],
         round_updated: *simul_count,
     });
 
     let out = loop {
-        match grid[(i + vi) as usize][(j + vj) as usize] {
+        match grid[guard + heading] {
             LabEntry::OutOfBounds => {
                 break false;
             }
             LabEntry::Vacant | LabEntry::Visited | LabEntry::Starting => {
-                i += vi;
-                j += vj;
+                guard += heading;
             }
             LabEntry::Obstacle {
                 ref mut hits,
                 ref mut round_updated,
             } => {
                 if round_updated == simul_count {
-                    if std::mem::replace(&mut hits[dir], true) {
+                    if std::mem::replace(&mut hits[heading.index()], true) {
                         break true;
                     }
                 } else {
                     *hits = [false; 4];
-                    hits[dir] = true;
+                    hits[heading.index()] = true;
                     *round_updated = *simul_count;
                 }
 
-                (vi, vj) = (vj, -vi);
-                dir = (dir + 1) % 4;
+                heading = heading.turn_clockwise();
             }
         }
     };
-    grid[obj_i][obj_j] = original;
+    grid[obstacle] = original;
     *simul_count += 1;
     out
 }
 
-pub fn day6(mut grid: Vec<Vec<LabEntry>>, mut i: i32, mut j: i32) -> (u64, u64) {
-    let cols = grid[0].len();
-    for row in grid.iter_mut() {
-        row.splice(0..0, [LabEntry::OutOfBounds]);
-        row.extend_from_slice(&[LabEntry::OutOfBounds]);
-    }
-    grid.splice(0..0, [vec![LabEntry::OutOfBounds; cols + 2]]);
-    grid.extend([vec![LabEntry::OutOfBounds; cols + 2]]);
-    i += 1;
-    j += 1;
-
+/// Walk the guard out of the (padded) lab, returning the number of visited
+/// positions and, if `count_cycles` is set, the number of positions where a new
+/// obstacle would trap the guard in a loop.
+fn patrol(grid: &mut Grid<LabEntry>, mut guard: Position, count_cycles: bool) -> (u64, u64) {
     let mut visited_counter = 1;
     let mut cycle_counter = 0;
-    let mut vi = -1;
-    let mut vj = 0;
-    let mut dir = 0;
+    let mut heading = Direction::North;
     let mut simul_count = 0;
     loop {
-        match grid[(i + vi) as usize][(j + vj) as usize] {
+        match grid[guard + heading] {
             LabEntry::OutOfBounds => break,
             LabEntry::Vacant => {
-                if simulate_obstacle(&mut grid, i, j, vi, vj, dir, &mut simul_count) {
+                if count_cycles && simulate_obstacle(grid, guard, heading, &mut simul_count) {
                     cycle_counter += 1;
                 }
-                i += vi;
-                j += vj;
-                grid[i as usize][j as usize] = LabEntry::Visited;
+                guard += heading;
+                grid[guard] = LabEntry::Visited;
                 visited_counter += 1;
             }
             LabEntry::Visited | LabEntry::Starting => {
-                i += vi;
-                j += vj;
+                guard += heading;
             }
             LabEntry::Obstacle { .. } => {
-                (vi, vj) = (vj, -vi);
-                dir = (dir + 1) % 4;
+                heading = heading.turn_clockwise();
             }
         }
     }
     (visited_counter, cycle_counter)
 }
 
+/// Assumes that grid is padded and that the guard starts at `guard`.
+pub fn day6_part1(mut grid: Grid<LabEntry>, guard: Position) -> u64 {
+    patrol(&mut grid, guard, false).0
+}
+
+/// Assumes that grid is padded and that the guard starts at `guard`.
+pub fn day6_part2(mut grid: Grid<LabEntry>, guard: Position) -> u64 {
+    patrol(&mut grid, guard, true).1
+}
+
+/// Assumes that grid is padded and that the guard starts at `guard`.
+pub fn day6(grid: Grid<LabEntry>, guard: Position) -> (u64, u64) {
+    (day6_part1(grid.clone(), guard), day6_part2(grid, guard))
+}
+
 #[derive(PartialEq, Eq)]
 enum EqSolvable {
     No,
@@ -620,37 +750,42 @@ fn is_solvable(eq: &[u64], current_index: usize, total: u64) -> EqSolvable {
     }
 }
 
+fn total_solvable(eqs: &[(u64, Vec<u64>)], allow_concat: bool) -> u64 {
+    eqs.iter()
+        .filter(|(total, eq)| match is_solvable(eq, eq.len() - 1, *total) {
+            EqSolvable::No => false,
+            EqSolvable::WithArith => true,
+            EqSolvable::WithConcat => allow_concat,
+        })
+        .map(|(total, _)| total)
+        .sum()
+}
+
+pub fn day7_part1(eqs: &[(u64, Vec<u64>)]) -> u64 {
+    total_solvable(eqs, false)
+}
+
+pub fn day7_part2(eqs: &[(u64, Vec<u64>)]) -> u64 {
+    total_solvable(eqs, true)
+}
+
 pub fn day7(eqs: Vec<(u64, Vec<u64>)>) -> (u64, u64) {
-    eqs.into_iter()
-        .map(|(total, eq)| (total, is_solvable(&eq, eq.len() - 1, total)))
-        .fold(
-            (0, 0),
-            |(with_arith, with_concat), (total, result)| match result {
-                EqSolvable::No => (with_arith, with_concat),
-                EqSolvable::WithArith => (with_arith + total, with_concat + total),
-                EqSolvable::WithConcat => (with_arith, with_concat + total),
-            },
-        )
+    (day7_part1(&eqs), day7_part2(&eqs))
 }
 
 fn count_antinodes_no_resonance(
-    antennas: &HashMap<u8, Vec<(i32, i32)>>,
+    antennas: &HashMap<u8, Vec<Position>>,
     rows: i32,
     cols: i32,
 ) -> usize {
     let mut antinode_positions = HashSet::new();
     for antennas in antennas.values() {
-        for (num, (i1, j1)) in antennas.iter().enumerate() {
-            for (i2, j2) in &antennas[num + 1..] {
-                let i_a1 = 2 * i1 - i2;
-                let j_a1 = 2 * j1 - j2;
-                let i_a2 = 2 * i2 - i1;
-                let j_a2 = 2 * j2 - j1;
-                if (0..rows).contains(&i_a1) && (0..cols).contains(&j_a1) {
-                    antinode_positions.insert((i_a1, j_a1));
-                }
-                if (0..rows).contains(&i_a2) && (0..cols).contains(&j_a2) {
-                    antinode_positions.insert((i_a2, j_a2));
+        for (num, &p1) in antennas.iter().enumerate() {
+            for &p2 in &antennas[num + 1..] {
+                for antinode in [p1 + (p1 - p2), p2 + (p2 - p1)] {
+                    if antinode.in_bounds(rows, cols) {
+                        antinode_positions.insert(antinode);
+                    }
                 }
             }
         }
@@ -659,34 +794,23 @@ fn count_antinodes_no_resonance(
 }
 
 fn count_antinodes_with_resonance(
-    antennas: &HashMap<u8, Vec<(i32, i32)>>,
+    antennas: &HashMap<u8, Vec<Position>>,
     rows: i32,
     cols: i32,
 ) -> usize {
     let mut antinode_positions = HashSet::new();
     for antennas in antennas.values() {
-        for (num, (i1, j1)) in antennas.iter().enumerate() {
+        for (num, &p1) in antennas.iter().enumerate() {
             if antennas.len() > 1 {
-                antinode_positions.insert((*i1, *j1));
+                antinode_positions.insert(p1);
             }
-            for (i2, j2) in &antennas[num + 1..] {
-                let mut vi = i2 - i1;
-                let mut vj = j2 - j1;
-                let mut new_i = i2 + vi;
-                let mut new_j = j2 + vj;
-                while (0..rows).contains(&new_i) && (0..cols).contains(&new_j) {
-                    antinode_positions.insert((new_i, new_j));
-                    new_i += vi;
-                    new_j += vj;
-                }
-                vi *= -1;
-                vj *= -1;
-                new_i = i1 + vi;
-                new_j = j1 + vj;
-                while (0..rows).contains(&new_i) && (0..cols).contains(&new_j) {
-                    antinode_positions.insert((new_i, new_j));
-                    new_i += vi;
-                    new_j += vj;
+            for &p2 in &antennas[num + 1..] {
+                for (start, v) in [(p2, p2 - p1), (p1, p1 - p2)] {
+                    let mut antinode = start + v;
+                    while antinode.in_bounds(rows, cols) {
+                        antinode_positions.insert(antinode);
+                        antinode += v;
+                    }
                 }
             }
         }
@@ -694,10 +818,18 @@ fn count_antinodes_with_resonance(
     antinode_positions.len()
 }
 
-pub fn day8(antennas: &HashMap<u8, Vec<(i32, i32)>>, rows: i32, cols: i32) -> (usize, usize) {
+pub fn day8_part1(antennas: &HashMap<u8, Vec<Position>>, rows: i32, cols: i32) -> usize {
+    count_antinodes_no_resonance(antennas, rows, cols)
+}
+
+pub fn day8_part2(antennas: &HashMap<u8, Vec<Position>>, rows: i32, cols: i32) -> usize {
+    count_antinodes_with_resonance(antennas, rows, cols)
+}
+
+pub fn day8(antennas: &HashMap<u8, Vec<Position>>, rows: i32, cols: i32) -> (usize, usize) {
     (
-        count_antinodes_no_resonance(antennas, rows, cols),
-        count_antinodes_with_resonance(antennas, rows, cols),
+        day8_part1(antennas, rows, cols),
+        day8_part2(antennas, rows, cols),
     )
 }
 
@@ -826,103 +958,159 @@ fn checksum_nonbreaking(layout: &[u8]) -> u64 {
     checksum
 }
 
-pub fn day9(layout: Vec<u8>) -> (u64, u64) {
+pub fn day9_part1(layout: &[u8]) -> u64 {
     assert!(layout.len() % 2 == 1);
 
-    (checksum_breaking(&layout), checksum_nonbreaking(&layout))
-}
-
-pub fn get_score(
-    terrain: &[Vec<u8>],
-    visited: &mut [Vec<u64>],
-    i: usize,
-    j: usize,
-    round: u64,
-) -> u64 {
-    if visited[i][j] == round {
-        0
-    } else if terrain[i][j] == 9 {
-        visited[i][j] = round;
-        1
-    } else {
-        let mut out = 0;
-        let neighbors = [(i - 1, j), (i + 1, j), (i, j - 1), (i, j + 1)];
-        for (ni, nj) in neighbors {
-            if terrain[ni][nj] == terrain[i][j] + 1 {
-                out += get_score(terrain, visited, ni, nj, round);
-            }
-        }
-        visited[i][j] = round;
-        out
-    }
+    checksum_breaking(layout)
 }
 
-pub fn count_paths_up(
-    terrain: &[Vec<u8>],
-    num_paths_to_top: &mut [Vec<Option<u64>>],
-    i: usize,
-    j: usize,
-) -> u64 {
-    if let Some(paths) = num_paths_to_top[i][j] {
-        paths
-    } else if terrain[i][j] == 9 {
-        num_paths_to_top[i][j] = Some(1);
-        1
-    } else {
-        let mut paths = 0;
-        let neighbors = [(i - 1, j), (i + 1, j), (i, j - 1), (i, j + 1)];
-        for (ni, nj) in neighbors {
-            if terrain[ni][nj] == terrain[i][j] + 1 {
-                paths += count_paths_up(terrain, num_paths_to_top, ni, nj);
-            }
-        }
-        num_paths_to_top[i][j] = Some(paths);
-        paths
-    }
+pub fn day9_part2(layout: &[u8]) -> u64 {
+    assert!(layout.len() % 2 == 1);
+
+    checksum_nonbreaking(layout)
 }
 
-#[allow(clippy::needless_range_loop)]
-pub fn day10(mut terrain: Vec<Vec<u8>>) -> (u64, u64) {
-    let cols = terrain[0].len();
-    for row in terrain.iter_mut() {
-        row.splice(0..0, [u8::MAX]);
-        row.extend_from_slice(&[u8::MAX]);
+pub fn day9(layout: Vec<u8>) -> (u64, u64) {
+    (day9_part1(&layout), day9_part2(&layout))
+}
+
+/// The positions one step higher than `pos`. Assumes that terrain is padded.
+fn uphill(terrain: &Grid<u8>) -> impl Fn(Position) -> Vec<Position> + '_ {
+    |pos| {
+        Direction::ALL
+            .map(|direction| pos + direction)
+            .into_iter()
+            .filter(|&next| terrain[next] == terrain[pos] + 1)
+            .collect()
     }
-    terrain.splice(0..0, [vec![u8::MAX; cols + 2]]);
-    terrain.extend([vec![u8::MAX; cols + 2]]);
+}
+
+/// Assumes that terrain is padded.
+fn total_score(terrain: &Grid<u8>) -> u64 {
+    let uphill = uphill(terrain);
+    terrain
+        .inner_positions()
+        .map(Position::from)
+        .filter(|&pos| terrain[pos] == 0)
+        .map(|pos| {
+            let peaks = reachable(&uphill, pos)
+                .into_iter()
+                .filter(|&x| terrain[x] == 9);
+            peaks.count() as u64
+        })
+        .sum()
+}
 
-    let rows = terrain.len();
-    let cols = terrain[0].len();
+/// Assumes that terrain is padded.
+fn total_paths(terrain: &Grid<u8>) -> u64 {
+    let uphill = uphill(terrain);
+    let mut paths = PathCounter::new(&uphill, |pos| terrain[pos] == 9);
+    terrain
+        .inner_positions()
+        .map(Position::from)
+        .filter(|&pos| terrain[pos] == 0)
+        .map(|pos| paths.count(pos))
+        .sum()
+}
 
-    let mut round = 1;
-    let mut visited = vec![vec![0; cols]; rows];
+/// Assumes that terrain is padded.
+pub fn day10_part1(terrain: &Grid<u8>) -> u64 {
+    total_score(terrain)
+}
 
-    let mut part1 = 0;
-    for i in 1..rows - 1 {
-        for j in 1..cols - 1 {
-            if terrain[i][j] == 0 {
-                part1 += get_score(&terrain, &mut visited, i, j, round);
-                round += 1;
-            }
+/// Assumes that terrain is padded.
+pub fn day10_part2(terrain: &Grid<u8>) -> u64 {
+    total_paths(terrain)
+}
+
+/// Assumes that terrain is padded.
+pub fn day10(terrain: &Grid<u8>) -> (u64, u64) {
+    (day10_part1(terrain), day10_part2(terrain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::Rng;
+
+    /// Whether removing some set of at most `k` levels makes the report safe,
+    /// trying every such set.
+    fn safe_after_any_removal(report: &[i8], k: usize, steps: StepRange) -> bool {
+        (0u32..1 << report.len()).any(|removed| {
+            let kept = (0..report.len())
+                .filter(|&i| removed & (1 << i) == 0)
+                .map(|i| report[i])
+                .collect::<Vec<_>>();
+            removed.count_ones() as usize <= k && check_report_safety(&kept, steps)
+        })
+    }
+
+    #[test]
+    fn k_mismatch_matches_exhaustive_removal() {
+        let mut rng = Rng::new(0);
+        for _ in 0..20000 {
+            let report = (0..rng.below(9))
+                .map(|_| {
+                    if rng.one_in(20) {
+                        rng.below(256) as u8 as i8
+                    } else {
+                        rng.between(0, 12) as i8
+                    }
+                })
+                .collect::<Vec<_>>();
+            let k = rng.below(4) as usize;
+            let steps = StepRange::new(rng.below(2) as i64, rng.between(1, 4) as i64).unwrap();
+            assert_eq!(
+                check_report_safety_k_mismatch(&report, k, steps),
+                safe_after_any_removal(&report, k, steps),
+                "{report:?} with k = {k} and {steps:?}"
+            );
         }
     }
 
-    let mut num_paths_to_top = vec![vec![None; cols]; rows];
+    #[test]
+    fn step_range_rejects_empty_ranges() {
+        assert_eq!(StepRange::new(1, 3), Some(StepRange::default()));
+        assert!(StepRange::new(0, 0).is_some());
+        assert_eq!(StepRange::new(5, 2), None);
+        assert_eq!(StepRange::new(-1, 2), None);
+    }
 
-    num_paths_to_top[0] = vec![Some(0); cols];
-    num_paths_to_top[rows - 1] = vec![Some(0); cols];
-    for i in 1..rows - 1 {
-        num_paths_to_top[i][0] = Some(0);
-        num_paths_to_top[i][cols - 1] = Some(0);
+    #[test]
+    fn k_mismatch_allows_any_k() {
+        let report = [1, 2, 7, 8, 9];
+        let steps = StepRange::default();
+        assert!(check_report_safety_k_mismatch(&report, usize::MAX, steps));
+        assert_eq!(
+            explain_report(&report, usize::MAX, steps),
+            ReportSafety::SafeWithout(vec![0, 1])
+        );
     }
 
-    let mut part2 = 0;
-    for i in 1..rows - 1 {
-        for j in 1..cols - 1 {
-            if terrain[i][j] == 0 {
-                part2 += count_paths_up(&terrain, &mut num_paths_to_top, i, j);
-            }
-        }
+    #[test]
+    fn day3_sums_past_u32() {
+        let memory = b"mul(999,999)".repeat(5000);
+        assert_eq!(day3_part1(&memory), 999 * 999 * 5000);
+    }
+
+    #[test]
+    fn step_range_saturates_at_the_level_type() {
+        let wide = StepRange::new(0, 1000).unwrap();
+        assert!(wide.contains(i8::MAX));
+        assert!(wide.contains(1000i16));
+        let far = StepRange::new(200, 300).unwrap();
+        assert!(!far.contains(i8::MAX));
+        assert!(far.contains(200i16));
+        assert!(check_report_safety(&[0i8, 100, i8::MAX], wide));
+    }
+
+    #[test]
+    fn combined_days_match_their_parts() {
+        use crate::{examples, parsers::*};
+        let (rules, updates) = day5_parser(examples::DAY5.input, &mut vec![]).unwrap();
+        let parts = (day5_part1(&rules, &updates), day5_part2(&rules, &updates));
+        assert_eq!(day5(rules, updates), parts);
+        let eqs = day7_parser(examples::DAY7.input, &mut vec![]).unwrap();
+        assert_eq!(day7(eqs.clone()), (day7_part1(&eqs), day7_part2(&eqs)));
     }
-    (part1, part2)
 }
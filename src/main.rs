@@ -1,94 +1,385 @@
 use advent_of_code::{
-    day1, day2, day3, day4, day5, day6, day7, day8, day9, day10,
-    parsers::{
-        day1_parser, day2_parser, day3_parser, day4_parser, day5_parser, day6_parser, day7_parser,
-        day8_parser, day9_parser, day10_parser,
-    },
+    bench::bench,
+    errors::{ErrorSummary, ParseError},
+    generate::{Params, Rng},
+    parsers::Source,
+    report::{Format, Report},
+    solutions::{DAYS, Entry, Part, Run, find},
+    verify::parse_expected,
+};
+use anyhow::{Context, Result, anyhow, bail};
+use clap::{Args, Command, CommandFactory, FromArgMatches, Parser, Subcommand};
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    panic::{AssertUnwindSafe, catch_unwind},
+    path::{Path, PathBuf},
+    process::ExitCode,
 };
-use anyhow::Result;
-use clap::{Parser, Subcommand, command};
-use std::path::PathBuf;
 
 #[derive(Parser)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Read inputs exactly as given, without removing a byte order mark, CRs,
+    /// trailing whitespace or trailing blank lines
+    #[arg(long, global = true)]
+    strict: bool,
 }
 
 #[derive(Subcommand, Debug)]
 enum Commands {
-    Day1 { file: Option<PathBuf> },
-    Day2 { file: Option<PathBuf> },
-    Day3 { file: Option<PathBuf> },
-    Day4 { file: Option<PathBuf> },
-    Day5 { file: Option<PathBuf> },
-    Day6 { file: Option<PathBuf> },
-    Day7 { file: Option<PathBuf> },
-    Day8 { file: Option<PathBuf> },
-    Day9 { file: Option<PathBuf> },
-    Day10 { file: Option<PathBuf> },
-}
-
-fn main() -> Result<()> {
-    let args = Cli::parse();
-
-    match args.command {
-        Commands::Day1 { file } => {
-            let (list1, list2) = day1_parser(file)?;
-            let (difference, score) = day1(list1, list2);
-            println!("{difference}, {score}");
+    /// Run every day against the `dayN.txt` files found in a directory
+    All {
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+        /// Count a day that produced warnings as failed
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+    /// Check the answers of each day against an expected-answers file
+    Verify {
+        /// A file with a `[dayN]` table of `part1`/`part2` answers per day
+        #[arg(required_unless_present = "examples")]
+        expected: Option<PathBuf>,
+        /// Check every day against its embedded puzzle example instead
+        #[arg(long, conflicts_with = "expected")]
+        examples: bool,
+        /// Where to find `dayN.txt` for days that do not set an `input`
+        #[arg(short, long, default_value = ".")]
+        dir: PathBuf,
+    },
+    /// Time the parse and solve phases of a day over repeated runs
+    Bench {
+        /// The day to run, e.g. `day6`
+        day: String,
+        file: PathBuf,
+        /// How many runs to record
+        #[arg(short, long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+        runs: u32,
+        /// How many runs to discard before recording
+        #[arg(short, long, default_value_t = 3)]
+        warmup: u32,
+        /// Only compute the given part
+        #[arg(short, long)]
+        part: Option<Part>,
+        /// How to print the statistics
+        #[arg(short, long, value_enum, default_value_t)]
+        format: Format,
+    },
+    /// Write a random well-formed input for a day to stdout
+    Generate {
+        /// The day to generate an input for, e.g. `day6`
+        day: String,
+        /// The same seed always gives the same input
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Lines of input, rows of a grid, or digits of a disk map
+        #[arg(short, long)]
+        size: Option<usize>,
+        /// Values per line, or columns of a grid
+        #[arg(short, long)]
+        width: Option<usize>,
+    },
+}
+
+#[derive(Args, Debug)]
+struct DayArgs {
+    file: Option<PathBuf>,
+    /// Run on the example from the puzzle text instead of an input
+    #[arg(short, long, conflicts_with = "file")]
+    example: bool,
+    /// Only compute the given part
+    #[arg(short, long)]
+    part: Option<Part>,
+    /// How to print the answers
+    #[arg(short, long, value_enum, default_value_t)]
+    format: Format,
+    /// Print how long parsing and solving took to stderr
+    #[arg(short, long)]
+    time: bool,
+    /// Fail instead of answering if the input produced any warnings
+    #[arg(long)]
+    deny_warnings: bool,
+}
+
+fn cli() -> Command {
+    DAYS.iter().fold(Cli::command(), |cmd, day| {
+        cmd.subcommand((day.args)(DayArgs::augment_args(Command::new(day.name))))
+    })
+}
+
+/// Run a day, turning a panic in its parser or solver into an error so that
+/// the remaining days still get a chance to run.
+fn run_day(day: &Entry, source: Source<'_>, part: Option<Part>) -> Result<Run> {
+    catch_unwind(AssertUnwindSafe(|| (day.run)(source, part, None))).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|x| x.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Err(anyhow!("panicked: {message}"))
+    })
+}
+
+/// Describe an error for the user, showing the offending line for errors in
+/// the puzzle input.
+fn render_error(err: &anyhow::Error) -> String {
+    if let Some(summary) = err.downcast_ref::<ErrorSummary>() {
+        let mut out = summary
+            .errors
+            .iter()
+            .map(|tracked| render_error(&tracked.error))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        out += &format!(
+            "\n\nerror: found {} error(s) in the input",
+            summary.errors.len()
+        );
+        out
+    } else if let Some(parse_err) = err.downcast_ref::<ParseError>() {
+        parse_err.render()
+    } else {
+        format!("error: {err:#}")
+    }
+}
+
+fn print_warnings(run: &Run, prefix: &str) {
+    for warning in &run.warnings {
+        eprintln!("{prefix}warning: {warning}");
+    }
+}
+
+/// Fail if a run produced warnings and they are being denied.
+fn deny_warnings(run: &Run, deny: bool) -> Result<()> {
+    if deny && !run.warnings.is_empty() {
+        bail!("{} warning(s) denied", run.warnings.len());
+    }
+    Ok(())
+}
+
+fn display_answer(answer: Option<u64>) -> String {
+    answer.map_or_else(|| "-".to_string(), |x| x.to_string())
+}
+
+fn all(dir: &Path, deny: bool, strict: bool) -> Result<()> {
+    let mut failures = vec![];
+    let mut found = false;
+
+    println!(
+        "{:<6} {:>16} {:>16} {:>12} {:>12}",
+        "day", "part1", "part2", "parse", "solve"
+    );
+    for day in &DAYS {
+        let file = dir.join(format!("{}.txt", day.name));
+        if !file.is_file() {
+            continue;
+        }
+        found = true;
+        let run = run_day(day, Source::File(file).strict(strict), None).and_then(|run| {
+            print_warnings(&run, &format!("{}: ", day.name));
+            deny_warnings(&run, deny)?;
+            Ok(run)
+        });
+        match run {
+            Ok(run) => println!(
+                "{:<6} {:>16} {:>16} {:>12} {:>12}",
+                day.name,
+                display_answer(run.part1),
+                display_answer(run.part2),
+                format!("{:.2?}", run.parse_time),
+                format!("{:.2?}", run.solve_time),
+            ),
+            Err(err) => {
+                println!("{:<6} {:>16}", day.name, "failed");
+                failures.push((day.name, err));
+            }
         }
-        Commands::Day2 { file } => {
-            let lists = day2_parser(file)?;
-            let (count_no_mismatch, count_one_mismatch) = day2(lists);
-            println!("{count_no_mismatch}, {count_one_mismatch}");
+    }
+
+    if !found {
+        bail!("No dayN.txt inputs found in {}", dir.display());
+    }
+    if !failures.is_empty() {
+        eprintln!();
+        for (name, err) in &failures {
+            eprintln!("{name}: {}", render_error(err));
         }
-        Commands::Day3 { file } => {
-            let prgm = day3_parser(file)?;
-            let val_no_cond = day3(&prgm, false);
-            let val_cond = day3(&prgm, true);
-            println!("{val_no_cond}, {val_cond}");
+        bail!("{} day(s) failed", failures.len());
+    }
+    Ok(())
+}
+
+/// One day's expected answers and the input to check them against.
+struct Check {
+    name: String,
+    source: Source<'static>,
+    part1: Option<u64>,
+    part2: Option<u64>,
+}
+
+fn expected_file_checks(expected: &Path, dir: &Path) -> Result<Vec<Check>> {
+    let text = fs::read_to_string(expected).context("Error opening expected answers file")?;
+    Ok(parse_expected(&text)?
+        .into_iter()
+        .map(|expected| Check {
+            source: Source::File(
+                expected
+                    .input
+                    .unwrap_or_else(|| dir.join(format!("{}.txt", expected.name))),
+            ),
+            name: expected.name,
+            part1: expected.part1,
+            part2: expected.part2,
+        })
+        .collect())
+}
+
+fn example_checks() -> Vec<Check> {
+    DAYS.iter()
+        .map(|day| Check {
+            name: day.name.to_string(),
+            source: Source::Example(day.example.input),
+            part1: Some(day.example.part1),
+            part2: Some(day.example.part2),
+        })
+        .collect()
+}
+
+fn verify(checks: Vec<Check>, strict: bool) -> Result<()> {
+    let mut failures = 0;
+
+    for check in checks {
+        let Some(day) = find(&check.name) else {
+            println!("{:<6} unknown day", check.name);
+            failures += 1;
+            continue;
+        };
+        let part = match (check.part1, check.part2) {
+            (Some(_), None) => Some(Part::One),
+            (None, Some(_)) => Some(Part::Two),
+            _ => None,
+        };
+
+        let run = match run_day(day, check.source.strict(strict), part) {
+            Ok(run) => run,
+            Err(err) => {
+                println!("{:<6} {}", day.name, render_error(&err));
+                failures += 1;
+                continue;
+            }
+        };
+        for (label, want, got) in [
+            ("part1", check.part1, run.part1),
+            ("part2", check.part2, run.part2),
+        ] {
+            let Some(want) = want else {
+                continue;
+            };
+            if got == Some(want) {
+                println!("{:<6} {label} ok", day.name);
+            } else {
+                println!(
+                    "{:<6} {label} FAIL: expected {want}, got {}",
+                    day.name,
+                    display_answer(got)
+                );
+                failures += 1;
+            }
         }
-        Commands::Day4 { file } => {
-            let grid = day4_parser(file)?;
-            let (word_count, cross_count) = day4(grid);
-            println!("{word_count} {cross_count}");
+    }
+
+    if failures > 0 {
+        bail!("{failures} check(s) failed");
+    }
+    Ok(())
+}
+
+fn try_main() -> Result<()> {
+    let matches = cli().get_matches();
+    let strict = matches.get_flag("strict");
+
+    if let Some((day, day_matches)) = matches
+        .subcommand()
+        .and_then(|(name, day_matches)| Some((find(name)?, day_matches)))
+    {
+        let args = DayArgs::from_arg_matches(day_matches)?;
+        let source = if args.example {
+            Source::Example(day.example.input)
+        } else {
+            args.file.into()
         }
-        Commands::Day5 { file } => {
-            let (rules, updates) = day5_parser(file);
-            let (correct_middle, sorted_middle) = day5(rules, updates);
-            println!("{correct_middle} {sorted_middle}");
+        .strict(strict);
+        let run = (day.run)(source.clone(), args.part, Some(day_matches))?;
+        if args.format != Format::Json || args.deny_warnings {
+            print_warnings(&run, "");
         }
-        Commands::Day6 { file } => {
-            let (grid, i, j) = day6_parser(file);
-            let (visited_counter, cycle_counter) = day6(grid, i, j);
-            println!("{visited_counter}, {cycle_counter}");
+        deny_warnings(&run, args.deny_warnings)?;
+        if args.format != Format::Json {
+            for line in &run.explanation {
+                println!("{line}");
+            }
         }
-        Commands::Day7 { file } => {
-            let eqs = day7_parser(file);
-            let (with_arith, with_concat) = day7(eqs);
-            println!("{with_arith}, {with_concat}");
+        let report = Report {
+            day: day.day,
+            input: &source,
+            run: &run,
+        };
+        println!("{}", report.render(args.format));
+        if args.time {
+            eprintln!("parse: {:?}, solve: {:?}", run.parse_time, run.solve_time);
         }
-        Commands::Day8 { file } => {
-            let (antennas, rows, cols) = day8_parser(file);
-            let (num_no_resonance, num_with_resonance) = day8(&antennas, rows, cols);
-            println!("{num_no_resonance}, {num_with_resonance}");
+        return Ok(());
+    }
+
+    match Cli::from_arg_matches(&matches)?.command {
+        Commands::All { dir, deny_warnings } => all(&dir, deny_warnings, strict),
+        Commands::Verify {
+            expected,
+            examples,
+            dir,
+        } => {
+            if examples {
+                verify(example_checks(), strict)
+            } else {
+                // clap requires `expected` unless `--examples` is given
+                verify(expected_file_checks(&expected.unwrap(), &dir)?, strict)
+            }
         }
-        Commands::Day9 { file } => {
-            let layout = day9_parser(file);
-            let (checksum_breaking, checksum_nonbreaking) = day9(layout);
-            println!("{checksum_breaking}, {checksum_nonbreaking}");
+        Commands::Bench {
+            day,
+            file,
+            runs,
+            warmup,
+            part,
+            format,
+        } => {
+            let day = find(&day).ok_or_else(|| anyhow!("Unknown day `{day}`"))?;
+            let bench = bench(day, &file, strict, part, runs as usize, warmup as usize)?;
+            println!("{}", bench.render(format));
+            Ok(())
         }
-        Commands::Day10 { file } => {
-            let terrain = day10_parser(file);
-            let (score, num_paths) = day10(terrain);
-            println!("{score}, {num_paths}");
+        Commands::Generate {
+            day,
+            seed,
+            size,
+            width,
+        } => {
+            let day = find(&day).ok_or_else(|| anyhow!("Unknown day `{day}`"))?;
+            let mut out = BufWriter::new(io::stdout().lock());
+            (day.generate)(&mut Rng::new(seed), Params { size, width }, &mut out)?;
+            out.flush()?;
+            Ok(())
         }
     }
-    Ok(())
+}
 
-    // let (rules, updates) = day5_parser(Some("day5.txt".into()));
-    // let (correct_middle, sorted_middle) = day5(rules, updates);
-    // println!("{correct_middle} {sorted_middle}");
-    // Ok(())
+fn main() -> ExitCode {
+    match try_main() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", render_error(&err));
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -1,179 +1,802 @@
 use anyhow::{Context, Result, anyhow};
+use flate2::read::MultiGzDecoder;
 use std::{
     collections::HashMap,
     fs::File,
-    io::{BufRead, BufReader, Read},
+    io::{self, BufRead, BufReader, Read},
+    num::ParseIntError,
+    ops::Range,
     path::PathBuf,
+    str::FromStr,
+    thread,
 };
 
-use crate::LabEntry;
+use crate::{
+    LabEntry, Reports,
+    errors::{
+        ErrorTracker, ParseError, ParseErrorKind, ScanErrors, SyncErrorTracker, Unless, Warning,
+    },
+    geometry::Position,
+    graph::topological_sort,
+    grid::Grid,
+};
 
-pub fn get_reader(path: Option<PathBuf>) -> Result<BufReader<Box<dyn std::io::Read>>> {
-    Ok(if let Some(ref file_path) = path {
-        BufReader::new(Box::new(
-            File::open(file_path).context("Error opening input file")?,
-        ))
-    } else {
-        BufReader::new(Box::new(std::io::stdin()))
-    })
+/// Where a parser reads its puzzle input from. Every parser also accepts a
+/// `&str` or `&[u8]` holding the input itself.
+#[derive(Clone, Debug)]
+pub enum Source<'a> {
+    Stdin,
+    File(PathBuf),
+    /// A puzzle example embedded in the binary, see [`crate::examples`]
+    Example(&'static str),
+    /// An input already in memory, taken as text even if it looks compressed
+    Bytes(&'a [u8]),
+    /// Another source, read exactly as given without the normalization done
+    /// by [`get_reader`]
+    Strict(Box<Source<'a>>),
 }
 
-pub fn day1_parser(file: Option<PathBuf>) -> Result<(Vec<u32>, Vec<u32>)> {
-    let mut list1 = vec![];
-    let mut list2 = vec![];
+impl<'a> Source<'a> {
+    /// The source, to be read exactly as given if `strict`.
+    pub fn strict(self, strict: bool) -> Source<'a> {
+        if strict {
+            Source::Strict(Box::new(self))
+        } else {
+            self
+        }
+    }
+}
 
-    for line in get_reader(file)?.lines() {
-        let vals = line?
-            .split_ascii_whitespace()
-            .map(str::parse::<u32>)
-            .collect::<Result<Vec<_>, _>>()?;
-        let [val1, val2] = vals
-            .try_into()
-            .map_err(|_| anyhow!("Row must have only two values"))?;
-        list1.push(val1);
-        list2.push(val2);
+impl From<Option<PathBuf>> for Source<'_> {
+    fn from(file: Option<PathBuf>) -> Self {
+        match file {
+            Some(file_path) => Source::File(file_path),
+            None => Source::Stdin,
+        }
     }
-    Ok((list1, list2))
 }
 
-pub fn day2_parser(file: Option<PathBuf>) -> Result<Vec<Vec<i8>>> {
-    let mut reports = vec![];
+impl From<PathBuf> for Source<'_> {
+    fn from(file: PathBuf) -> Self {
+        Source::File(file)
+    }
+}
 
-    for line in get_reader(file)?.lines() {
-        let report = line?
-            .split_ascii_whitespace()
-            .map(str::parse::<i8>)
-            .collect::<Result<Vec<_>, _>>()?;
-        reports.push(report);
+impl<'a> From<&'a str> for Source<'a> {
+    fn from(text: &'a str) -> Self {
+        Source::Bytes(text.as_bytes())
     }
-    Ok(reports)
 }
 
-pub fn day3_parser(file: Option<PathBuf>) -> Result<Vec<u8>> {
-    let lines = get_reader(file)?
-        .lines()
-        .map(|x| x.map_err(|_| anyhow!("Failed to read line")))
-        .collect::<Result<Vec<_>>>()?;
-    Ok(lines.into_iter().flat_map(|x| x.into_bytes()).collect())
+impl<'a> From<&'a [u8]> for Source<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Source::Bytes(bytes)
+    }
+}
+
+/// Reads through to `inner`, removing a leading UTF-8 byte order mark, the
+/// trailing whitespace of every line (including the `\r` of a CRLF line
+/// ending) and any blank lines at the end. If not `trim_whitespace`, only the
+/// `\r` is removed from the end of each line.
+struct Normalized<R> {
+    inner: R,
+    trim_whitespace: bool,
+    started: bool,
+    blank_lines: usize,
+    line: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: BufRead> Normalized<R> {
+    fn new(inner: R, trim_whitespace: bool) -> Self {
+        Normalized {
+            inner,
+            trim_whitespace,
+            started: false,
+            blank_lines: 0,
+            line: vec![],
+            pos: 0,
+        }
+    }
+
+    /// Load the next non-blank line along with the blank lines before it,
+    /// returning false at the end of the input.
+    fn next_line(&mut self) -> io::Result<bool> {
+        self.line.clear();
+        self.pos = 0;
+        loop {
+            let mut raw = vec![];
+            if self.inner.read_until(b'\n', &mut raw)? == 0 {
+                return Ok(false);
+            }
+            if !std::mem::replace(&mut self.started, true)
+                && let Some(rest) = raw.strip_prefix(b"\xef\xbb\xbf")
+            {
+                raw = rest.to_vec();
+            }
+            let ends_line = raw.ends_with(b"\n");
+            let len = if self.trim_whitespace {
+                raw.trim_ascii_end().len()
+            } else {
+                let line = raw.strip_suffix(b"\n").unwrap_or(&raw);
+                line.strip_suffix(b"\r").unwrap_or(line).len()
+            };
+            raw.truncate(len);
+            if raw.is_empty() {
+                self.blank_lines += 1;
+                continue;
+            }
+
+            self.line
+                .resize(std::mem::take(&mut self.blank_lines), b'\n');
+            self.line.append(&mut raw);
+            if ends_line {
+                self.line.push(b'\n');
+            }
+            return Ok(true);
+        }
+    }
+}
+
+impl<R: BufRead> Read for Normalized<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.line.len() {
+            if !self.next_line()? {
+                return Ok(0);
+            }
+        }
+        let n = buf.len().min(self.line.len() - self.pos);
+        buf[..n].copy_from_slice(&self.line[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const ZSTD_MAGIC: &[u8] = b"\x28\xb5\x2f\xfd";
+
+/// Decompress `reader` on the fly if it starts like a gzip or zstd stream.
+fn decompressed<'a>(mut reader: impl Read + 'a) -> Result<Box<dyn Read + 'a>> {
+    // Read the magic bytes up front and put them back in front of the rest, as
+    // a pipe may hand them over a few at a time
+    let mut magic = Vec::with_capacity(ZSTD_MAGIC.len());
+    (&mut reader)
+        .take(ZSTD_MAGIC.len() as u64)
+        .read_to_end(&mut magic)
+        .context("Error reading input")?;
+    let reader = io::Cursor::new(magic).chain(reader);
+    let prefix = reader.get_ref().0.get_ref();
+
+    Ok(if prefix.starts_with(GZIP_MAGIC) {
+        Box::new(MultiGzDecoder::new(reader))
+    } else if prefix.starts_with(ZSTD_MAGIC) {
+        Box::new(zstd::Decoder::new(reader).context("Error starting zstd decompression")?)
+    } else {
+        Box::new(reader)
+    })
+}
+
+/// A reader over the input, decompressed if it is a file or stdin holding gzip
+/// or zstd compressed data, and normalized as described on [`Normalized`]
+/// unless it is a [`Source::Strict`].
+pub fn get_reader<'a>(source: impl Into<Source<'a>>) -> Result<BufReader<Box<dyn Read + 'a>>> {
+    open(source.into(), true)
+}
+
+/// Like [`get_reader`], but keeping the whitespace at the end of each line, for
+/// days where it can be part of the input.
+fn get_untrimmed_reader<'a>(
+    source: impl Into<Source<'a>>,
+) -> Result<BufReader<Box<dyn Read + 'a>>> {
+    open(source.into(), false)
+}
+
+fn open<'a>(source: Source<'a>, trim_whitespace: bool) -> Result<BufReader<Box<dyn Read + 'a>>> {
+    Ok(match source {
+        Source::Strict(source) => BufReader::new(open_raw(*source)?),
+        source => {
+            let reader = BufReader::new(open_raw(source)?);
+            BufReader::new(Box::new(Normalized::new(reader, trim_whitespace)))
+        }
+    })
+}
+
+/// The input as it is stored, decompressed if need be.
+fn open_raw<'a>(source: Source<'a>) -> Result<Box<dyn Read + 'a>> {
+    Ok(match source {
+        Source::Stdin => decompressed(io::stdin())?,
+        Source::File(file_path) => {
+            decompressed(File::open(file_path).context("Error opening input file")?)?
+        }
+        Source::Example(text) => Box::new(text.as_bytes()),
+        Source::Bytes(bytes) => Box::new(bytes),
+        Source::Strict(source) => open_raw(*source)?,
+    })
+}
+
+/// The lines of the input, stopping after the first read error since a failing
+/// reader may keep failing forever.
+pub(crate) fn read_lines<'a>(
+    source: impl Into<Source<'a>>,
+) -> Result<impl Iterator<Item = std::io::Result<String>>> {
+    let mut failed = false;
+    Ok(get_reader(source)?.lines().map_while(move |line| {
+        if failed {
+            return None;
+        }
+        failed = line.is_err();
+        Some(line)
+    }))
+}
+
+/// Parse independent lines on several threads, one chunk of lines per thread,
+/// collecting the errors from every chunk. `parse_line` is given the index of
+/// the line in the whole input, and somewhere to put the warnings of its chunk,
+/// which are added to `warnings` in line order.
+fn parse_lines_parallel<T: Send>(
+    lines: &[String],
+    message: &str,
+    warnings: &mut Vec<Warning>,
+    parse_line: impl Fn(usize, &str, &mut Vec<Warning>) -> Result<T> + Sync,
+) -> Result<Vec<T>> {
+    let errors: SyncErrorTracker = SyncErrorTracker::new();
+    let threads = thread::available_parallelism().map_or(1, |x| x.get());
+    let chunk_size = lines.len().div_ceil(threads).max(1);
+
+    let parsed = thread::scope(|scope| {
+        let handles = lines
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(k, chunk)| {
+                let (errors, parse_line) = (&errors, &parse_line);
+                let first_index = k * chunk_size;
+                scope.spawn(move || {
+                    let mut warnings = vec![];
+                    let parsed = chunk
+                        .iter()
+                        .enumerate()
+                        .map(|(i, line)| parse_line(first_index + i, line, &mut warnings))
+                        .scan_errors_from(errors, message, first_index)
+                        .collect::<Vec<_>>();
+                    (parsed, warnings)
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                let (parsed, chunk_warnings) = handle.join().unwrap();
+                warnings.extend(chunk_warnings);
+                parsed
+            })
+            .collect::<Vec<_>>()
+    });
+    parsed.unless(errors)
+}
+
+/// Pair each piece of a split line with its byte range in the line.
+fn spanned<'a>(
+    line: &'a str,
+    pieces: impl Iterator<Item = &'a str>,
+) -> impl Iterator<Item = (Range<usize>, &'a str)> {
+    pieces.map(move |piece| {
+        // `piece` is a subslice of `line`, so this is its byte offset
+        let start = piece.as_ptr() as usize - line.as_ptr() as usize;
+        (start..start + piece.len(), piece)
+    })
+}
+
+/// Split a line on ASCII whitespace, keeping the byte range of each field.
+fn fields(line: &str) -> impl Iterator<Item = (Range<usize>, &str)> {
+    spanned(line, line.split_ascii_whitespace())
+}
+
+fn parse_number<T: FromStr<Err = ParseIntError>>(
+    field: &str,
+    line_index: usize,
+    columns: Range<usize>,
+    line: &str,
+) -> Result<T, ParseError> {
+    field.parse().map_err(|err| {
+        ParseError::new(
+            ParseErrorKind::InvalidNumber(err),
+            line_index,
+            columns,
+            line,
+        )
+    })
+}
+
+pub fn day1_parser<'a>(source: impl Into<Source<'a>>) -> Result<(Vec<u32>, Vec<u32>)> {
+    let errors: ErrorTracker = ErrorTracker::new();
+    let (list1, list2) = read_lines(source)?
+        .enumerate()
+        .map(|(i, line)| {
+            let line = line?;
+            let vals = fields(&line)
+                .map(|(columns, field)| parse_number::<u32>(field, i, columns, &line))
+                .collect::<Result<Vec<_>, _>>()?;
+            let [val1, val2] = vals.try_into().map_err(|vals: Vec<_>| {
+                // Point at any extra values, or just past the end if some are missing
+                let columns = match fields(&line).nth(2) {
+                    Some((columns, _)) => columns.start..line.len(),
+                    None => line.len()..line.len(),
+                };
+                let kind = ParseErrorKind::WrongValueCount {
+                    expected: 2,
+                    found: vals.len(),
+                };
+                ParseError::new(kind, i, columns, &line)
+            })?;
+            Ok((val1, val2))
+        })
+        .scan_errors(&errors, "Invalid pair of location IDs")
+        .unzip();
+    (list1, list2).unless(errors)
+}
+
+/// Convert every level to `T`, if they all fit.
+fn narrow_reports<T: TryFrom<i64>>(reports: &[Vec<i64>]) -> Option<Vec<Vec<T>>> {
+    reports
+        .iter()
+        .map(|report| report.iter().map(|&x| T::try_from(x).ok()).collect())
+        .collect()
+}
+
+/// Levels may be any `i64`, and are held in the narrowest type that fits them
+/// all.
+pub fn day2_parser<'a>(source: impl Into<Source<'a>>) -> Result<Reports> {
+    let errors: ErrorTracker = ErrorTracker::new();
+    let reports = read_lines(source)?
+        .enumerate()
+        .map(|(i, line)| {
+            let line = line?;
+            let report = fields(&line)
+                .map(|(columns, field)| parse_number::<i64>(field, i, columns, &line))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(report)
+        })
+        .scan_errors(&errors, "Invalid report")
+        .collect::<Vec<_>>();
+    let reports = reports.unless(errors)?;
+    Ok(narrow_reports(&reports)
+        .map(Reports::I8)
+        .or_else(|| narrow_reports(&reports).map(Reports::I16))
+        .or_else(|| narrow_reports(&reports).map(Reports::I32))
+        .unwrap_or(Reports::I64(reports)))
+}
+
+/// Lines are joined without a separator, so whitespace at the end of a line is
+/// kept rather than let an instruction form across it.
+pub fn day3_parser<'a>(source: impl Into<Source<'a>>) -> Result<Vec<u8>> {
+    let mut memory = vec![];
+    get_untrimmed_reader(source)?
+        .read_to_end(&mut memory)
+        .map_err(|_| anyhow!("Failed to read line"))?;
+    memory.retain(|&c| c != b'\n');
+    Ok(memory)
+}
+
+pub fn day4_parser<'a>(
+    source: impl Into<Source<'a>>,
+    warnings: &mut Vec<Warning>,
+) -> Result<Grid<u8>> {
+    Grid::read_padded(
+        source,
+        b'.',
+        "Invalid row of the word search",
+        warnings,
+        |_, _, c| match c {
+            c if c.is_ascii() => Ok(c as u8),
+            c => Err(ParseErrorKind::UnknownCell(c)),
+        },
+    )
 }
 
-pub fn day4_parser(file: Option<PathBuf>) -> Result<Vec<Vec<u8>>> {
-    let lines = get_reader(file)?
-        .lines()
-        .map(|x| x.map_err(|_| anyhow!("Failed to read line")))
-        .collect::<Result<Vec<_>>>()?;
-    Ok(lines.into_iter().map(|x| x.into_bytes()).collect())
+/// Parse a page number, which must fit in the 100x100 rules matrix.
+fn parse_page(
+    field: &str,
+    line_index: usize,
+    columns: Range<usize>,
+    line: &str,
+) -> Result<usize, ParseError> {
+    let page = parse_number::<usize>(field, line_index, columns.clone(), line)?;
+    if page >= 100 {
+        let kind = ParseErrorKind::ValueTooLarge { max: 99 };
+        return Err(ParseError::new(kind, line_index, columns, line));
+    }
+    Ok(page)
 }
 
-pub fn day5_parser(file: Option<PathBuf>) -> (Vec<(usize, usize)>, Vec<Vec<usize>>) {
-    let mut lines_iter = get_reader(file).unwrap().lines().map(|x| x.unwrap());
-    let rules = lines_iter
-        .by_ref()
-        .take_while(|line| !line.trim().is_empty())
-        .map(|line| {
-            (
-                line[0..2].parse::<usize>().unwrap(),
-                line[3..5].parse::<usize>().unwrap(),
-            )
+#[allow(clippy::type_complexity)]
+pub fn day5_parser<'a>(
+    source: impl Into<Source<'a>>,
+    warnings: &mut Vec<Warning>,
+) -> Result<(Vec<(usize, usize)>, Vec<Vec<usize>>)> {
+    let errors: ErrorTracker = ErrorTracker::new();
+    let mut rules = vec![];
+    let mut updates = vec![];
+    let mut rule_lines = HashMap::new();
+    let mut successors = vec![vec![]; 100];
+    let mut in_rules = true;
+
+    // Rules and updates are handled in a single pass so that every error is
+    // logged against its line in the whole input
+    read_lines(source)?
+        .enumerate()
+        .map(|(i, line)| {
+            let line = line?;
+            if in_rules {
+                if line.trim().is_empty() {
+                    in_rules = false;
+                    return Ok(());
+                }
+                let (from, to) = line.split_once('|').ok_or_else(|| {
+                    let kind = ParseErrorKind::MissingSeparator { separator: "|" };
+                    ParseError::new(kind, i, 0..line.len(), &line)
+                })?;
+                let rule = (
+                    parse_page(from, i, 0..from.len(), &line)?,
+                    parse_page(to, i, from.len() + 1..line.len(), &line)?,
+                );
+                if let Some(first_i) = rule_lines.insert(rule, i) {
+                    let message = format!("duplicate rule, first given on line {}", first_i + 1);
+                    warnings.push(Warning::at(i, None, message));
+                }
+                rules.push(rule);
+                successors[rule.0].push(rule.1);
+            } else {
+                let update = spanned(&line, line.split(','))
+                    .map(|(columns, field)| parse_page(field, i, columns, &line))
+                    .collect::<Result<Vec<_>, _>>()?;
+                // Every rule comes before the updates, so they are all known
+                let rules = |page: usize| successors[page].iter().copied();
+                if topological_sort(update.iter().copied(), &rules).is_none() {
+                    let kind = ParseErrorKind::RuleCycle;
+                    return Err(ParseError::new(kind, i, 0..line.len(), &line).into());
+                }
+                updates.push(update);
+            }
+            Ok(())
         })
-        .collect();
-    let updates = lines_iter
-        .map(|line| line.split(',').map(|x| x.parse().unwrap()).collect())
-        .collect();
-    (rules, updates)
-}
-
-pub fn day6_parser(file: Option<PathBuf>) -> (Vec<Vec<LabEntry>>, i32, i32) {
-    let mut guard_i = 0;
-    let mut guard_j = 0;
-    let grid = get_reader(file)
-        .unwrap()
-        .lines()
+        .scan_errors(&errors, "Invalid rule or update")
+        .for_each(drop);
+    (rules, updates).unless(errors)
+}
+
+/// The guard's position is given in the padded grid.
+pub fn day6_parser<'a>(source: impl Into<Source<'a>>) -> Result<(Grid<LabEntry>, Position)> {
+    let errors: ErrorTracker = ErrorTracker::new();
+    let mut guard = None;
+    let mut last_line = (0, String::new());
+
+    let rows = read_lines(source)?
         .enumerate()
-        .map(|(i, x)| {
-            x.unwrap()
-                .as_bytes()
-                .iter()
-                .enumerate()
-                .map(|(j, &x)| match x {
-                    b'.' => LabEntry::Vacant,
-                    b'#' => LabEntry::Obstacle {
+        .map(|(i, line)| {
+            let line = line?;
+            let row = line
+                .char_indices()
+                .map(|(j, c)| match c {
+                    '.' => Ok(LabEntry::Vacant),
+                    '#' => Ok(LabEntry::Obstacle {
                         hits: [false; 4],
                         round_updated: 0,
-                    },
-                    b'^' => {
-                        guard_i = i;
-                        guard_j = j;
-                        LabEntry::Starting
+                    }),
+                    '^' => {
+                        if let Some((first_i, first_j)) = guard {
+                            let kind = ParseErrorKind::MultipleGuards {
+                                first_line: first_i + 1,
+                                first_column: first_j + 1,
+                            };
+                            return Err(ParseError::new(kind, i, j..j + 1, &line));
+                        }
+                        guard = Some((i, j));
+                        Ok(LabEntry::Starting)
+                    }
+                    c => {
+                        let kind = ParseErrorKind::UnknownCell(c);
+                        Err(ParseError::new(kind, i, j..j + c.len_utf8(), &line))
                     }
-                    _ => panic!(),
                 })
-                .collect()
+                .collect::<Result<Vec<_>, _>>()?;
+            last_line = (i, line);
+            Ok(row)
         })
-        .collect();
-    (grid, guard_i as i32, guard_j as i32)
-}
-
-pub fn day7_parser(file: Option<PathBuf>) -> Vec<(u64, Vec<u64>)> {
-    get_reader(file)
-        .unwrap()
-        .lines()
-        .map(|x| {
-            let line = x.unwrap();
-            let [total, rest]: [String; 2] = line
-                .split(": ")
-                .map(|x| x.to_string())
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap();
-            let total: u64 = total.parse().unwrap();
-            let eq = rest.split(' ').map(|x| x.parse::<u64>().unwrap()).collect();
-            (total, eq)
-        })
-        .collect()
+        .scan_errors(&errors, "Invalid row of the lab")
+        .collect::<Vec<_>>();
+    errors.check_status()?;
+
+    let Some((guard_i, guard_j)) = guard else {
+        let (i, line) = last_line;
+        let end = line.len();
+        return Err(ParseError::new(ParseErrorKind::NoGuard, i, end..end, &line).into());
+    };
+    let grid = Grid::padded(rows, LabEntry::OutOfBounds);
+    Ok((grid, Position::from((guard_i + 1, guard_j + 1))))
 }
 
+pub fn day7_parser<'a>(
+    source: impl Into<Source<'a>>,
+    warnings: &mut Vec<Warning>,
+) -> Result<Vec<(u64, Vec<u64>)>> {
+    let lines = read_lines(source)?.collect::<Result<Vec<_>, _>>()?;
+    parse_lines_parallel(&lines, "Invalid equation", warnings, |i, line, _| {
+        let (total, rest) = line.split_once(": ").ok_or_else(|| {
+            let kind = ParseErrorKind::MissingSeparator { separator: ": " };
+            ParseError::new(kind, i, 0..line.len(), line)
+        })?;
+        let total = parse_number::<u64>(total, i, 0..total.len(), line)?;
+        let eq = spanned(line, rest.split(' '))
+            .map(|(columns, field)| parse_number::<u64>(field, i, columns, line))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((total, eq))
+    })
+}
+
+/// A ragged map is as wide as its longest row.
 #[allow(clippy::type_complexity)]
-pub fn day8_parser(file: Option<PathBuf>) -> (HashMap<u8, Vec<(i32, i32)>>, i32, i32) {
-    let mut antennas: HashMap<u8, Vec<(i32, i32)>> = HashMap::new();
+pub fn day8_parser<'a>(
+    source: impl Into<Source<'a>>,
+    warnings: &mut Vec<Warning>,
+) -> Result<(HashMap<u8, Vec<Position>>, i32, i32)> {
+    let mut antennas: HashMap<u8, Vec<Position>> = HashMap::new();
     let mut rows = 0;
     let mut cols = 0;
-    for (i, line) in get_reader(file)
-        .unwrap()
-        .lines()
-        .map(|x| x.unwrap().into_bytes())
-        .enumerate()
-    {
+    let mut first_width = None;
+    for (i, line) in get_reader(source)?.lines().enumerate() {
+        let line = line?;
+        let width = *first_width.get_or_insert(line.len());
+        if line.len() != width {
+            warnings.push(Warning::at(
+                i,
+                None,
+                format!(
+                    "row has {} columns but the first row has {width}",
+                    line.len()
+                ),
+            ));
+        }
         rows = i + 1;
-        cols = line.len();
-        for (j, &frequency) in line.iter().enumerate() {
-            if frequency != b'.' {
+        cols = cols.max(line.len());
+        for (j, frequency) in line.char_indices() {
+            if !frequency.is_ascii() {
+                let kind = ParseErrorKind::UnknownCell(frequency);
+                let columns = j..j + frequency.len_utf8();
+                return Err(ParseError::new(kind, i, columns, &line).into());
+            }
+            if frequency != '.' {
                 antennas
-                    .entry(frequency)
+                    .entry(frequency as u8)
                     .or_default()
-                    .push((i as i32, j as i32))
+                    .push(Position::from((i, j)))
             }
         }
     }
-    (antennas, rows as i32, cols as i32)
+    Ok((antennas, rows as i32, cols as i32))
 }
 
-pub fn day9_parser(file: Option<PathBuf>) -> Vec<u8> {
-    get_reader(file)
-        .unwrap()
-        .bytes()
-        .filter_map(|x| (x.unwrap() as char).to_digit(10).map(|x| x as u8))
-        .collect::<Vec<_>>()
+pub fn day9_parser<'a>(
+    source: impl Into<Source<'a>>,
+    warnings: &mut Vec<Warning>,
+) -> Result<Vec<u8>> {
+    let mut layout = vec![];
+    let (mut i, mut j) = (0, 0);
+    let mut ignored = 0;
+    let mut first_ignored = None;
+    // The last line with anything on it, for locating an error at the end
+    let mut line = vec![];
+    let mut last_line = (0, vec![]);
+    for byte in get_reader(source)?.bytes() {
+        let byte = byte?;
+        if byte == b'\n' {
+            if !line.is_empty() {
+                last_line = (i, std::mem::take(&mut line));
+            }
+        } else {
+            line.push(byte);
+        }
+        if let Some(digit) = (byte as char).to_digit(10) {
+            layout.push(digit as u8);
+        } else if !byte.is_ascii_whitespace() {
+            ignored += 1;
+            first_ignored.get_or_insert((i, j, byte));
+        }
+        if byte == b'\n' {
+            (i, j) = (i + 1, 0);
+        } else {
+            j += 1;
+        }
+    }
+    if let Some((i, j, byte)) = first_ignored {
+        warnings.push(Warning::at(
+            i,
+            Some(j),
+            format!(
+                "ignored {ignored} non-digit byte(s), starting with `{}`",
+                (byte as char).escape_debug()
+            ),
+        ));
+    }
+    if layout.len() % 2 == 0 {
+        let (i, line) = if line.is_empty() {
+            last_line
+        } else {
+            (i, line)
+        };
+        let line = String::from_utf8_lossy(&line);
+        let end = line.len();
+        let kind = ParseErrorKind::EvenDiskMap {
+            digits: layout.len(),
+        };
+        return Err(ParseError::new(kind, i, end..end, &line).into());
+    }
+    Ok(layout)
 }
 
-pub fn day10_parser(file: Option<PathBuf>) -> Vec<Vec<u8>> {
-    let lines = get_reader(file)
-        .unwrap()
-        .lines()
-        .map(|x| x.unwrap())
-        .collect::<Vec<_>>();
-    lines
-        .into_iter()
-        .map(|x| x.chars().map(|x| x.to_digit(10).unwrap() as u8).collect())
-        .collect()
+pub fn day10_parser<'a>(
+    source: impl Into<Source<'a>>,
+    warnings: &mut Vec<Warning>,
+) -> Result<Grid<u8>> {
+    let message = "Invalid row of the map";
+    Grid::read_padded(source, u8::MAX, message, warnings, |_, _, c| {
+        c.to_digit(10)
+            .map(|x| x as u8)
+            .ok_or(ParseErrorKind::InvalidDigit(c))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{errors::ErrorSummary, examples};
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+
+    fn read_all(source: Source<'_>, trim_whitespace: bool) -> String {
+        let mut text = String::new();
+        open(source, trim_whitespace)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    #[test]
+    fn normalizing_removes_bom_crs_and_trailing_whitespace() {
+        let text = "\u{feff}1 2  \r\n3\t\r\n\u{feff}4";
+        assert_eq!(read_all(text.into(), true), "1 2\n3\n\u{feff}4");
+    }
+
+    #[test]
+    fn normalizing_drops_only_trailing_blank_lines() {
+        let text = "1|2\r\n\r\n3,4\r\n \r\n\n\t\n";
+        assert_eq!(read_all(text.into(), true), "1|2\n\n3,4\n");
+    }
+
+    #[test]
+    fn untrimmed_normalizing_keeps_trailing_spaces() {
+        let text = "mul(1 \r\n2,3) \n\n";
+        assert_eq!(read_all(text.into(), false), "mul(1 \n2,3) \n");
+    }
+
+    #[test]
+    fn strict_sources_are_read_as_given() {
+        let text = "\u{feff}1 2 \r\n\r\n";
+        assert_eq!(read_all(Source::from(text).strict(true), true), text);
+    }
+
+    #[test]
+    fn crlf_inputs_parse_like_lf_inputs() {
+        let crlf = examples::DAY5.input.replace('\n', "\r\n");
+        assert_eq!(
+            day5_parser(crlf.as_str(), &mut vec![]).unwrap(),
+            day5_parser(examples::DAY5.input, &mut vec![]).unwrap()
+        );
+    }
+
+    #[test]
+    fn strict_sources_keep_trailing_whitespace() {
+        let text = "190: 10 19 \r\n";
+        assert_eq!(day7_parser(text, &mut vec![]).unwrap(), vec![(190, vec![
+            10, 19
+        ])]);
+        assert!(day7_parser(Source::from(text).strict(true), &mut vec![]).is_err());
+    }
+
+    #[test]
+    fn rule_cycles_are_rejected() {
+        let err = day5_parser("1|2\n2|1\n\n1,3\n2,1,3\n", &mut vec![]).unwrap_err();
+        let summary = err.downcast_ref::<ErrorSummary>().unwrap();
+        let err = summary.errors[0]
+            .error
+            .downcast_ref::<ParseError>()
+            .unwrap();
+        assert!(matches!(err.kind, ParseErrorKind::RuleCycle));
+        assert_eq!(err.line, 5);
+    }
+
+    #[test]
+    fn disk_maps_must_end_with_a_file() {
+        assert_eq!(day9_parser("12345\n", &mut vec![]).unwrap(), vec![
+            1, 2, 3, 4, 5
+        ]);
+        for text in ["", "\n", "12\n", "1234\n\n"] {
+            let err = day9_parser(text, &mut vec![]).unwrap_err();
+            let err = err.downcast_ref::<ParseError>().unwrap();
+            assert!(matches!(err.kind, ParseErrorKind::EvenDiskMap { .. }));
+        }
+    }
+
+    #[test]
+    fn parallel_warnings_reach_the_caller() {
+        let lines = (0..100).map(|i| i.to_string()).collect::<Vec<_>>();
+        let mut warnings = vec![];
+        let parsed = parse_lines_parallel(
+            &lines,
+            "Invalid line",
+            &mut warnings,
+            |i, line, warnings| {
+                warnings.push(Warning::at(i, None, "seen"));
+                Ok(line.len())
+            },
+        )
+        .unwrap();
+        assert_eq!(parsed.len(), 100);
+        let lines_warned = warnings.iter().map(|x| x.line.unwrap()).collect::<Vec<_>>();
+        assert_eq!(lines_warned, (1..=100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn warnings_go_to_the_given_list() {
+        let mut warnings = vec![];
+        assert_eq!(day9_parser("1x2y3\n", &mut warnings).unwrap(), vec![
+            1, 2, 3
+        ]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!((warnings[0].line, warnings[0].column), (Some(1), Some(2)));
+    }
+
+    fn gzip(text: &str) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn decompress(reader: impl Read) -> String {
+        let mut text = String::new();
+        decompressed(reader)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    #[test]
+    fn compressed_inputs_are_decompressed() {
+        let text = examples::DAY1.input;
+        assert_eq!(decompress(gzip(text).as_slice()), text);
+        let zstd = zstd::encode_all(text.as_bytes(), 0).unwrap();
+        assert_eq!(decompress(zstd.as_slice()), text);
+    }
+
+    #[test]
+    fn every_gzip_member_is_decompressed() {
+        let mut members = gzip("3   4\n");
+        members.extend(gzip("4   3\n"));
+        assert_eq!(decompress(members.as_slice()), "3   4\n4   3\n");
+    }
+
+    #[test]
+    fn magic_bytes_split_across_reads_are_recognized() {
+        let compressed = gzip("3   4\n");
+        let (first, rest) = compressed.split_at(1);
+        assert_eq!(decompress(first.chain(rest)), "3   4\n");
+    }
+
+    #[test]
+    fn short_inputs_are_passed_through() {
+        for text in ["", "1", "1\n2", "\x1f"] {
+            assert_eq!(decompress(text.as_bytes()), text);
+        }
+    }
 }
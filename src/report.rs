@@ -0,0 +1,118 @@
+use clap::ValueEnum;
+use std::fmt::Write;
+
+use crate::{errors::Warning, parsers::Source, solutions::Run};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// The answers separated by a comma
+    #[default]
+    Plain,
+    /// A single JSON object with the answers, timings, input path, warnings and
+    /// any explanation
+    Json,
+    /// A single row of `day,part1,part2,parse_time_ns,solve_time_ns,input`
+    Csv,
+}
+
+/// How an input is described in machine-readable output: the file path, nothing
+/// for stdin, `<example>` for an embedded example, or `<bytes>` for an input
+/// already in memory.
+fn describe_input(input: &Source<'_>) -> Option<String> {
+    match input {
+        Source::Stdin => None,
+        Source::File(path) => Some(path.to_string_lossy().into_owned()),
+        Source::Example(_) => Some("<example>".to_string()),
+        Source::Bytes(_) => Some("<bytes>".to_string()),
+        Source::Strict(source) => describe_input(source),
+    }
+}
+
+/// Everything known about one day's run, ready to be rendered in any format.
+pub struct Report<'a> {
+    pub day: u8,
+    pub input: &'a Source<'a>,
+    pub run: &'a Run,
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub(crate) fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn json_answer(answer: Option<u64>) -> String {
+    answer.map_or_else(|| "null".to_string(), |x| x.to_string())
+}
+
+fn json_warning(warning: &Warning) -> String {
+    format!(
+        "{{\"line\":{},\"column\":{},\"message\":{}}}",
+        json_answer(warning.line.map(|x| x as u64)),
+        json_answer(warning.column.map(|x| x as u64)),
+        json_string(&warning.message),
+    )
+}
+
+impl Report<'_> {
+    pub fn render(&self, format: Format) -> String {
+        let run = self.run;
+        match format {
+            Format::Plain => match (run.part1, run.part2) {
+                (Some(part1), Some(part2)) => format!("{part1}, {part2}"),
+                (Some(answer), None) | (None, Some(answer)) => answer.to_string(),
+                (None, None) => String::new(),
+            },
+            Format::Json => format!(
+                "{{\"day\":{},\"input\":{},\"part1\":{},\"part2\":{},\"parse_time_ns\":{},\"solve_time_ns\":{},\"warnings\":[{}],\"explanation\":[{}]}}",
+                self.day,
+                describe_input(self.input).map_or_else(|| "null".to_string(), |x| json_string(&x)),
+                json_answer(run.part1),
+                json_answer(run.part2),
+                run.parse_time.as_nanos(),
+                run.solve_time.as_nanos(),
+                run.warnings
+                    .iter()
+                    .map(json_warning)
+                    .collect::<Vec<_>>()
+                    .join(","),
+                run.explanation
+                    .iter()
+                    .map(|x| json_string(x))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            Format::Csv => format!(
+                "{},{},{},{},{},{}",
+                self.day,
+                run.part1.map(|x| x.to_string()).unwrap_or_default(),
+                run.part2.map(|x| x.to_string()).unwrap_or_default(),
+                run.parse_time.as_nanos(),
+                run.solve_time.as_nanos(),
+                describe_input(self.input)
+                    .map(|x| csv_field(&x))
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+}
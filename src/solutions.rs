@@ -0,0 +1,461 @@
+use anyhow::{Result, bail};
+use clap::{ArgMatches, Args, Command, FromArgMatches, ValueEnum};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    LabEntry, Level, ReportSafety, Reports, StepRange, Trend, day1_part1, day1_part2, day2_part1,
+    day2_tolerant, day3_part1, day3_part2, day4_part1, day4_part2, day5_part1, day5_part2,
+    day6_part1, day6_part2, day7_part1, day7_part2, day8_part1, day8_part2, day9_part1, day9_part2,
+    day10_part1, day10_part2,
+    errors::Warning,
+    examples::{self, Example},
+    explain_report,
+    generate::{self, Generator},
+    geometry::Position,
+    grid::Grid,
+    parsers::{
+        Source, day1_parser, day2_parser, day3_parser, day4_parser, day5_parser, day6_parser,
+        day7_parser, day8_parser, day9_parser, day10_parser,
+    },
+};
+
+/// A single day's puzzle: how to parse its input and how to answer each part.
+pub trait Solution {
+    type Input;
+    /// Extra command-line options taken by the day's subcommand.
+    type Options: Args + FromArgMatches + Default;
+
+    /// Reject options that cannot be used together, before the input is read.
+    fn validate(_options: &Self::Options) -> Result<()> {
+        Ok(())
+    }
+
+    /// Anything suspicious but harmless about the input goes in `warnings`.
+    fn parse(source: Source<'_>, warnings: &mut Vec<Warning>) -> Result<Self::Input>;
+
+    fn part1(input: &Self::Input, options: &Self::Options) -> u64;
+
+    fn part2(input: &Self::Input, options: &Self::Options) -> u64;
+
+    /// A line for each line of the input describing how it counted towards
+    /// the answers, if the day can say and its options ask it to.
+    fn explain(_input: &Self::Input, _options: &Self::Options) -> Vec<String> {
+        vec![]
+    }
+}
+
+/// The options of a day that takes none.
+#[derive(Args, Clone, Debug, Default)]
+pub struct NoOptions {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Part {
+    #[value(name = "1")]
+    One,
+    #[value(name = "2")]
+    Two,
+}
+
+/// The answers for one day along with how long each phase took, anything
+/// suspicious noticed along the way and any explanation asked for. A part that
+/// was not requested is left as `None`.
+pub struct Run {
+    pub part1: Option<u64>,
+    pub part2: Option<u64>,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+    pub warnings: Vec<Warning>,
+    pub explanation: Vec<String>,
+}
+
+/// Parse the input and answer the requested part, or both parts if `part` is
+/// `None`. The day's options are read from `matches`, or left at their
+/// defaults if there are none.
+pub fn run<S: Solution>(
+    source: Source<'_>,
+    part: Option<Part>,
+    matches: Option<&ArgMatches>,
+) -> Result<Run> {
+    let options = match matches {
+        Some(matches) => S::Options::from_arg_matches(matches)?,
+        None => S::Options::default(),
+    };
+    S::validate(&options)?;
+    let mut warnings = vec![];
+    let start = Instant::now();
+    let input = S::parse(source, &mut warnings)?;
+    let parse_time = start.elapsed();
+
+    let start = Instant::now();
+    let part1 = (part != Some(Part::Two)).then(|| S::part1(&input, &options));
+    let part2 = (part != Some(Part::One)).then(|| S::part2(&input, &options));
+    let solve_time = start.elapsed();
+    let explanation = S::explain(&input, &options);
+
+    Ok(Run {
+        part1,
+        part2,
+        parse_time,
+        solve_time,
+        warnings,
+        explanation,
+    })
+}
+
+/// A registered day, with the solution type erased so the CLI can iterate over
+/// every day uniformly.
+pub struct Entry {
+    pub day: u8,
+    pub name: &'static str,
+    pub example: &'static Example,
+    /// Add the day's options to its subcommand
+    pub args: fn(Command) -> Command,
+    pub run: fn(Source<'_>, Option<Part>, Option<&ArgMatches>) -> Result<Run>,
+    pub generate: Generator,
+}
+
+const fn entry<S: Solution>(
+    day: u8,
+    name: &'static str,
+    example: &'static Example,
+    generate: Generator,
+) -> Entry {
+    Entry {
+        day,
+        name,
+        example,
+        args: S::Options::augment_args,
+        run: run::<S>,
+        generate,
+    }
+}
+
+pub static DAYS: [Entry; 10] = [
+    entry::<Day1>(1, "day1", &examples::DAY1, generate::day1),
+    entry::<Day2>(2, "day2", &examples::DAY2, generate::day2),
+    entry::<Day3>(3, "day3", &examples::DAY3, generate::day3),
+    entry::<Day4>(4, "day4", &examples::DAY4, generate::day4),
+    entry::<Day5>(5, "day5", &examples::DAY5, generate::day5),
+    entry::<Day6>(6, "day6", &examples::DAY6, generate::day6),
+    entry::<Day7>(7, "day7", &examples::DAY7, generate::day7),
+    entry::<Day8>(8, "day8", &examples::DAY8, generate::day8),
+    entry::<Day9>(9, "day9", &examples::DAY9, generate::day9),
+    entry::<Day10>(10, "day10", &examples::DAY10, generate::day10),
+];
+
+pub fn find(name: &str) -> Option<&'static Entry> {
+    DAYS.iter().find(|entry| entry.name == name)
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = (Vec<u32>, Vec<u32>);
+    type Options = NoOptions;
+
+    fn parse(source: Source<'_>, _warnings: &mut Vec<Warning>) -> Result<Self::Input> {
+        day1_parser(source)
+    }
+
+    fn part1((list1, list2): &Self::Input, _: &Self::Options) -> u64 {
+        day1_part1(list1.clone(), list2.clone())
+    }
+
+    fn part2((list1, list2): &Self::Input, _: &Self::Options) -> u64 {
+        day1_part2(list1.clone(), list2.clone())
+    }
+}
+
+pub struct Day2;
+
+#[derive(Args, Clone, Debug)]
+pub struct Day2Options {
+    /// How many bad levels part 2 may remove from a report
+    #[arg(long, default_value_t = 1)]
+    pub tolerance: usize,
+    /// The smallest safe step between neighbouring levels; 0 lets a report
+    /// hold level
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(i64).range(0..))]
+    pub min_step: i64,
+    /// The largest safe step between neighbouring levels
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(i64).range(0..))]
+    pub max_step: i64,
+    /// Print whether each report is safe, which levels make it safe, or where
+    /// it goes wrong
+    #[arg(long)]
+    pub explain: bool,
+}
+
+impl Day2Options {
+    /// Returns `None` if `min_step` is larger than `max_step`.
+    pub fn steps(&self) -> Option<StepRange> {
+        StepRange::new(self.min_step, self.max_step)
+    }
+}
+
+impl Default for Day2Options {
+    fn default() -> Self {
+        let steps = StepRange::default();
+        Day2Options {
+            tolerance: 1,
+            min_step: steps.min(),
+            max_step: steps.max(),
+            explain: false,
+        }
+    }
+}
+
+/// Evaluate `$body` with `$reports` bound to the reports at whichever level
+/// type they were parsed as.
+macro_rules! with_reports {
+    ($input:expr, |$reports:ident| $body:expr) => {
+        match $input {
+            Reports::I8($reports) => $body,
+            Reports::I16($reports) => $body,
+            Reports::I32($reports) => $body,
+            Reports::I64($reports) => $body,
+        }
+    };
+}
+
+fn explain_reports<T: Level>(
+    reports: &[Vec<T>],
+    tolerance: usize,
+    steps: StepRange,
+) -> Vec<String> {
+    reports
+        .iter()
+        .enumerate()
+        .map(|(n, report)| {
+            let verdict = match explain_report(report, tolerance, steps) {
+                ReportSafety::Safe => "safe".to_string(),
+                ReportSafety::SafeWithout(removed) => {
+                    let levels = removed
+                        .iter()
+                        .map(|&x| format!("{} ({})", x + 1, report[x]))
+                        .collect::<Vec<_>>();
+                    let plural = if levels.len() == 1 { "" } else { "s" };
+                    format!("safe without level{plural} {}", levels.join(", "))
+                }
+                ReportSafety::Unsafe { index, trend } => {
+                    let trend = match trend {
+                        Trend::Increasing => "increasing",
+                        Trend::Decreasing => "decreasing",
+                    };
+                    format!(
+                        "unsafe: levels {} and {} go from {} to {} while {trend}",
+                        index + 1,
+                        index + 2,
+                        report[index],
+                        report[index + 1],
+                    )
+                }
+            };
+            format!("line {}: {verdict}", n + 1)
+        })
+        .collect()
+}
+
+/// The step range of options already accepted by [`Day2::validate`].
+fn day2_steps(options: &Day2Options) -> StepRange {
+    options
+        .steps()
+        .expect("step range checked by Day2::validate")
+}
+
+impl Solution for Day2 {
+    type Input = Reports;
+    type Options = Day2Options;
+
+    fn validate(options: &Self::Options) -> Result<()> {
+        if options.steps().is_none() {
+            bail!(
+                "--min-step {} is larger than --max-step {}",
+                options.min_step,
+                options.max_step
+            );
+        }
+        Ok(())
+    }
+
+    fn parse(source: Source<'_>, _warnings: &mut Vec<Warning>) -> Result<Self::Input> {
+        day2_parser(source)
+    }
+
+    fn part1(reports: &Self::Input, options: &Self::Options) -> u64 {
+        let steps = day2_steps(options);
+        with_reports!(reports, |reports| day2_part1(reports, steps)) as u64
+    }
+
+    fn part2(reports: &Self::Input, options: &Self::Options) -> u64 {
+        let steps = day2_steps(options);
+        with_reports!(reports, |reports| {
+            day2_tolerant(reports, options.tolerance, steps)
+        }) as u64
+    }
+
+    fn explain(reports: &Self::Input, options: &Self::Options) -> Vec<String> {
+        if !options.explain {
+            return vec![];
+        }
+        let steps = day2_steps(options);
+        with_reports!(reports, |reports| {
+            explain_reports(reports, options.tolerance, steps)
+        })
+    }
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input = Vec<u8>;
+    type Options = NoOptions;
+
+    fn parse(source: Source<'_>, _warnings: &mut Vec<Warning>) -> Result<Self::Input> {
+        day3_parser(source)
+    }
+
+    fn part1(prgm: &Self::Input, _: &Self::Options) -> u64 {
+        day3_part1(prgm)
+    }
+
+    fn part2(prgm: &Self::Input, _: &Self::Options) -> u64 {
+        day3_part2(prgm)
+    }
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input = Grid<u8>;
+    type Options = NoOptions;
+
+    fn parse(source: Source<'_>, warnings: &mut Vec<Warning>) -> Result<Self::Input> {
+        day4_parser(source, warnings)
+    }
+
+    fn part1(grid: &Self::Input, _: &Self::Options) -> u64 {
+        day4_part1(grid)
+    }
+
+    fn part2(grid: &Self::Input, _: &Self::Options) -> u64 {
+        day4_part2(grid)
+    }
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = (Vec<(usize, usize)>, Vec<Vec<usize>>);
+    type Options = NoOptions;
+
+    fn parse(source: Source<'_>, warnings: &mut Vec<Warning>) -> Result<Self::Input> {
+        day5_parser(source, warnings)
+    }
+
+    fn part1((rules, updates): &Self::Input, _: &Self::Options) -> u64 {
+        day5_part1(rules, updates) as u64
+    }
+
+    fn part2((rules, updates): &Self::Input, _: &Self::Options) -> u64 {
+        day5_part2(rules, updates) as u64
+    }
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = (Grid<LabEntry>, Position);
+    type Options = NoOptions;
+
+    fn parse(source: Source<'_>, _warnings: &mut Vec<Warning>) -> Result<Self::Input> {
+        day6_parser(source)
+    }
+
+    fn part1((grid, guard): &Self::Input, _: &Self::Options) -> u64 {
+        day6_part1(grid.clone(), *guard)
+    }
+
+    fn part2((grid, guard): &Self::Input, _: &Self::Options) -> u64 {
+        day6_part2(grid.clone(), *guard)
+    }
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input = Vec<(u64, Vec<u64>)>;
+    type Options = NoOptions;
+
+    fn parse(source: Source<'_>, warnings: &mut Vec<Warning>) -> Result<Self::Input> {
+        day7_parser(source, warnings)
+    }
+
+    fn part1(eqs: &Self::Input, _: &Self::Options) -> u64 {
+        day7_part1(eqs)
+    }
+
+    fn part2(eqs: &Self::Input, _: &Self::Options) -> u64 {
+        day7_part2(eqs)
+    }
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = (HashMap<u8, Vec<Position>>, i32, i32);
+    type Options = NoOptions;
+
+    fn parse(source: Source<'_>, warnings: &mut Vec<Warning>) -> Result<Self::Input> {
+        day8_parser(source, warnings)
+    }
+
+    fn part1((antennas, rows, cols): &Self::Input, _: &Self::Options) -> u64 {
+        day8_part1(antennas, *rows, *cols) as u64
+    }
+
+    fn part2((antennas, rows, cols): &Self::Input, _: &Self::Options) -> u64 {
+        day8_part2(antennas, *rows, *cols) as u64
+    }
+}
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Input = Vec<u8>;
+    type Options = NoOptions;
+
+    fn parse(source: Source<'_>, warnings: &mut Vec<Warning>) -> Result<Self::Input> {
+        day9_parser(source, warnings)
+    }
+
+    fn part1(layout: &Self::Input, _: &Self::Options) -> u64 {
+        day9_part1(layout)
+    }
+
+    fn part2(layout: &Self::Input, _: &Self::Options) -> u64 {
+        day9_part2(layout)
+    }
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Grid<u8>;
+    type Options = NoOptions;
+
+    fn parse(source: Source<'_>, warnings: &mut Vec<Warning>) -> Result<Self::Input> {
+        day10_parser(source, warnings)
+    }
+
+    fn part1(terrain: &Self::Input, _: &Self::Options) -> u64 {
+        day10_part1(terrain)
+    }
+
+    fn part2(terrain: &Self::Input, _: &Self::Options) -> u64 {
+        day10_part2(terrain)
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::{Context, Result, anyhow, bail};
+use std::path::PathBuf;
+
+/// The known-good answers for one day, as read from an expected-answers file.
+#[derive(Debug, Default)]
+pub struct Expected {
+    pub name: String,
+    pub part1: Option<u64>,
+    pub part2: Option<u64>,
+    /// Overrides the default `dayN.txt` input location
+    pub input: Option<PathBuf>,
+}
+
+/// Strip a trailing `#` comment, ignoring any `#` inside a quoted string.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Parse an expected-answers file. The format is a small subset of TOML, with
+/// one table per day:
+///
+/// ```toml
+/// [day1]
+/// part1 = 11
+/// part2 = 31
+/// input = "inputs/day1.txt" # optional
+/// ```
+pub fn parse_expected(text: &str) -> Result<Vec<Expected>> {
+    let mut days: Vec<Expected> = vec![];
+
+    for (i, line) in text.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let line_no = i + 1;
+
+        if let Some(name) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+            let name = name.trim();
+            if days.iter().any(|x| x.name == name) {
+                bail!("Line {line_no}: duplicate table [{name}]");
+            }
+            days.push(Expected {
+                name: name.to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Line {line_no}: expected `key = value` or `[dayN]`"))?;
+        let (key, value) = (key.trim(), value.trim());
+        let day = days
+            .last_mut()
+            .ok_or_else(|| anyhow!("Line {line_no}: `{key}` appears before any [dayN] table"))?;
+
+        match key {
+            "part1" | "part2" => {
+                let answer = value
+                    .parse::<u64>()
+                    .with_context(|| format!("Line {line_no}: invalid answer `{value}`"))?;
+                if key == "part1" {
+                    day.part1 = Some(answer);
+                } else {
+                    day.part2 = Some(answer);
+                }
+            }
+            "input" => {
+                let path = value
+                    .strip_prefix('"')
+                    .and_then(|x| x.strip_suffix('"'))
+                    .ok_or_else(|| anyhow!("Line {line_no}: input must be a quoted string"))?;
+                day.input = Some(path.into());
+            }
+            _ => bail!("Line {line_no}: unknown key `{key}`"),
+        }
+    }
+    Ok(days)
+}
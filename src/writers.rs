@@ -0,0 +1,256 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use crate::{
+    LabEntry, Level, Reports,
+    geometry::Position,
+    grid::Grid,
+    parsers::{
+        day1_parser, day2_parser, day3_parser, day4_parser, day5_parser, day6_parser, day7_parser,
+        day8_parser, day9_parser, day10_parser,
+    },
+};
+
+// Each `dayN_write` is the inverse of `dayN_parser`: parsing what it writes
+// gives back the same input.
+
+pub fn day1_write(list1: &[u32], list2: &[u32], out: &mut impl Write) -> io::Result<()> {
+    for (val1, val2) in list1.iter().zip(list2) {
+        writeln!(out, "{val1}   {val2}")?;
+    }
+    Ok(())
+}
+
+fn write_reports<T: Level>(reports: &[Vec<T>], out: &mut impl Write) -> io::Result<()> {
+    for report in reports {
+        let levels = report.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        writeln!(out, "{}", levels.join(" "))?;
+    }
+    Ok(())
+}
+
+pub fn day2_write(reports: &Reports, out: &mut impl Write) -> io::Result<()> {
+    match reports {
+        Reports::I8(reports) => write_reports(reports, out),
+        Reports::I16(reports) => write_reports(reports, out),
+        Reports::I32(reports) => write_reports(reports, out),
+        Reports::I64(reports) => write_reports(reports, out),
+    }
+}
+
+/// Reading drops a byte order mark at the start and one `\r` at the end, so a
+/// memory starting with the one or ending with the other is written with an
+/// extra line break or `\r` to keep it.
+pub fn day3_write(memory: &[u8], out: &mut impl Write) -> io::Result<()> {
+    if memory.starts_with(b"\xef\xbb\xbf") {
+        writeln!(out)?;
+    }
+    out.write_all(memory)?;
+    if memory.ends_with(b"\r") {
+        out.write_all(b"\r")?;
+    }
+    writeln!(out)
+}
+
+/// Write the cells inside the padding, leaving out any `skip` cells so that a
+/// ragged row filled out with padding comes back out ragged.
+fn write_padded_grid<T>(
+    grid: &Grid<T>,
+    out: &mut impl Write,
+    mut cell: impl FnMut(Position, &T) -> Option<u8>,
+) -> io::Result<()> {
+    let inner_cols = grid.cols().saturating_sub(2);
+    for i in 1..grid.rows().saturating_sub(1) {
+        let mut line = grid.row(i)[1..=inner_cols]
+            .iter()
+            .enumerate()
+            .map_while(|(j, x)| cell(Position::from((i, j + 1)), x))
+            .collect::<Vec<_>>();
+        line.push(b'\n');
+        out.write_all(&line)?;
+    }
+    Ok(())
+}
+
+pub fn day4_write(grid: &Grid<u8>, out: &mut impl Write) -> io::Result<()> {
+    write_padded_grid(grid, out, |_, &x| Some(x))
+}
+
+pub fn day5_write(
+    rules: &[(usize, usize)],
+    updates: &[Vec<usize>],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for (from, to) in rules {
+        writeln!(out, "{from}|{to}")?;
+    }
+    writeln!(out)?;
+    for update in updates {
+        let pages = update.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        writeln!(out, "{}", pages.join(","))?;
+    }
+    Ok(())
+}
+
+/// The guard is written at `guard`, and visited cells are written as vacant.
+pub fn day6_write(grid: &Grid<LabEntry>, guard: Position, out: &mut impl Write) -> io::Result<()> {
+    write_padded_grid(grid, out, |pos, entry| match entry {
+        _ if pos == guard => Some(b'^'),
+        LabEntry::OutOfBounds => None,
+        LabEntry::Vacant | LabEntry::Visited | LabEntry::Starting => Some(b'.'),
+        LabEntry::Obstacle { .. } => Some(b'#'),
+    })
+}
+
+pub fn day7_write(eqs: &[(u64, Vec<u64>)], out: &mut impl Write) -> io::Result<()> {
+    for (total, eq) in eqs {
+        let values = eq.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        writeln!(out, "{total}: {}", values.join(" "))?;
+    }
+    Ok(())
+}
+
+/// Antennas outside the map are left out.
+pub fn day8_write(
+    antennas: &HashMap<u8, Vec<Position>>,
+    rows: i32,
+    cols: i32,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let mut map = Grid::new(rows.max(0) as usize, cols.max(0) as usize, b'.');
+    for (&frequency, positions) in antennas {
+        for pos in positions {
+            if pos.in_bounds(rows, cols) {
+                map[*pos] = frequency;
+            }
+        }
+    }
+    for i in 0..map.rows() {
+        out.write_all(map.row(i))?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+pub fn day9_write(layout: &[u8], out: &mut impl Write) -> io::Result<()> {
+    let mut line = layout.iter().map(|x| b'0' + x).collect::<Vec<_>>();
+    line.push(b'\n');
+    out.write_all(&line)
+}
+
+pub fn day10_write(terrain: &Grid<u8>, out: &mut impl Write) -> io::Result<()> {
+    write_padded_grid(terrain, out, |_, &x| (x <= 9).then_some(b'0' + x))
+}
+
+/// Parse `text`, then write out what was parsed and parse that again.
+macro_rules! roundtrip {
+    ($text:expr, $parse:expr, |$input:ident, $out:ident| $write:expr) => {{
+        let parse = $parse;
+        let Ok(first) = parse($text) else {
+            return false;
+        };
+        let written = {
+            let $input = &first;
+            let mut $out = vec![];
+            $write.unwrap();
+            $out
+        };
+        let second = parse(written.as_slice()).expect("written input does not parse");
+        assert_eq!(first, second, "written input parses differently");
+
+        let rewritten = {
+            let $input = &second;
+            let mut $out = vec![];
+            $write.unwrap();
+            $out
+        };
+        assert_eq!(written, rewritten, "written input is written differently");
+        true
+    }};
+}
+
+/// Check that writing out what `text` parses to as day `day`'s input, and
+/// parsing that, gives back the same input, and that writing it again gives
+/// the same text. Returns false if `text` does not parse, and panics if the
+/// round trip fails. Shared by the unit tests and the fuzz target.
+#[doc(hidden)]
+pub fn check_roundtrip(day: u8, text: &[u8]) -> bool {
+    match day {
+        1 => roundtrip!(text, day1_parser, |input, out| {
+            day1_write(&input.0, &input.1, &mut out)
+        }),
+        2 => roundtrip!(text, day2_parser, |input, out| day2_write(input, &mut out)),
+        3 => roundtrip!(text, day3_parser, |input, out| day3_write(input, &mut out)),
+        4 => roundtrip!(text, |text| day4_parser(text, &mut vec![]), |input, out| {
+            day4_write(input, &mut out)
+        }),
+        5 => roundtrip!(text, |text| day5_parser(text, &mut vec![]), |input, out| {
+            day5_write(&input.0, &input.1, &mut out)
+        }),
+        6 => roundtrip!(text, day6_parser, |input, out| {
+            day6_write(&input.0, input.1, &mut out)
+        }),
+        7 => roundtrip!(text, |text| day7_parser(text, &mut vec![]), |input, out| {
+            day7_write(input, &mut out)
+        }),
+        8 => roundtrip!(text, |text| day8_parser(text, &mut vec![]), |input, out| {
+            day8_write(&input.0, input.1, input.2, &mut out)
+        }),
+        9 => roundtrip!(text, |text| day9_parser(text, &mut vec![]), |input, out| {
+            day9_write(input, &mut out)
+        }),
+        10 => roundtrip!(
+            text,
+            |text| day10_parser(text, &mut vec![]),
+            |input, out| { day10_write(input, &mut out) }
+        ),
+        _ => panic!("no writer for day {day}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generate::{Params, Rng},
+        solutions::DAYS,
+    };
+
+    #[test]
+    fn examples_roundtrip() {
+        for day in &DAYS {
+            assert!(check_roundtrip(day.day, day.example.input.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn generated_inputs_roundtrip() {
+        let params = Params {
+            size: Some(20),
+            width: Some(12),
+        };
+        for seed in 0..10 {
+            for day in &DAYS {
+                let mut text = vec![];
+                (day.generate)(&mut Rng::new(seed), params, &mut text).unwrap();
+                assert!(
+                    check_roundtrip(day.day, &text),
+                    "day{} seed {seed}",
+                    day.day
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn awkward_inputs_roundtrip() {
+        assert!(check_roundtrip(3, b"mul(1,2)\r\r"));
+        assert!(check_roundtrip(3, b"\n\xef\xbb\xbfmul(1,2)\n"));
+        assert!(check_roundtrip(8, b"..#^..\n5..\n"));
+        assert!(check_roundtrip(8, b"..\n.....a\n"));
+        assert!(!check_roundtrip(4, "XM\u{fb}S\n".as_bytes()));
+        assert!(!check_roundtrip(8, "..\n.\u{fecb}\n".as_bytes()));
+    }
+}